@@ -0,0 +1,78 @@
+// Measures the cost of decoding frames for each supported sample format.
+// Usage:  `cargo bench`
+
+#[macro_use]
+extern crate criterion;
+use criterion::{BenchmarkId, Criterion, Throughput};
+
+extern crate wavefile;
+use wavefile::WaveFile;
+
+const SAMPLE_RATE : u32 = 44100;
+const CHANNELS    : u16 = 2;
+const SECONDS     : u32 = 10;
+
+// Builds a wavefile in memory holding `SECONDS` of silence.
+fn build_wave(format: u16, bits_per_sample: u16) -> Vec<u8> {
+  let block_align = CHANNELS * bits_per_sample / 8;
+  let data_size   = SAMPLE_RATE * SECONDS * block_align as u32;
+
+  let mut bytes = Vec::with_capacity(44 + data_size as usize);
+  bytes.extend_from_slice(b"RIFF");
+  bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+  bytes.extend_from_slice(b"WAVEfmt \x10\0\0\0");
+  bytes.extend_from_slice(&format.to_le_bytes());
+  bytes.extend_from_slice(&CHANNELS.to_le_bytes());
+  bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+  bytes.extend_from_slice(&(SAMPLE_RATE * block_align as u32).to_le_bytes());
+  bytes.extend_from_slice(&block_align.to_le_bytes());
+  bytes.extend_from_slice(&bits_per_sample.to_le_bytes());
+  bytes.extend_from_slice(b"data");
+  bytes.extend_from_slice(&data_size.to_le_bytes());
+  bytes.resize(44 + data_size as usize, 0);
+  bytes
+}
+
+fn bench_iter(c: &mut Criterion) {
+  let formats = [
+    ("pcm8",    1, 8),
+    ("pcm16",   1, 16),
+    ("pcm24",   1, 24),
+    ("pcm32",   1, 32),
+    ("float32", 3, 32),
+    ("float64", 3, 64)
+  ];
+
+  let mut group = c.benchmark_group("iter");
+  group.sample_size(10);
+
+  for &(name, format, bits_per_sample) in &formats {
+    let bytes = build_wave(format, bits_per_sample);
+    group.throughput(Throughput::Bytes(bytes.len() as u64 - 44));
+
+    let wav = WaveFile::from_vec(bytes).unwrap();
+    group.bench_function(BenchmarkId::from_parameter(name), |b| {
+      b.iter(|| wav.iter().count())
+    });
+  }
+
+  group.finish();
+}
+
+// There is no bulk frame reading API yet; this is the baseline any such API
+// would have to beat.
+fn bench_take(c: &mut Criterion) {
+  let bytes  = build_wave(1, 16);
+  let wav    = WaveFile::from_vec(bytes).unwrap();
+  let frames = 4096;
+
+  let mut group = c.benchmark_group("take");
+  group.throughput(Throughput::Bytes(frames as u64 * 4));
+  group.bench_function("iter_take_collect", |b| {
+    b.iter(|| wav.iter().take(frames).collect::<Vec<_>>())
+  });
+  group.finish();
+}
+
+criterion_group!(benches, bench_iter, bench_take);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+// This example prints a summary of a wavefile's properties, like `soxi`.
+// Usage:  `cargo run -- /path/to/foo.wav [--json]`
+//
+// Pass `-` as the path to read the wavefile from standard input, e.g.
+// `ffmpeg -i input.mp4 -f wav - | cargo run -- -`.
+
+
+extern crate argparse;
+use argparse::{ ArgumentParser, Store, StoreTrue };
+
+extern crate wavefile;
+use wavefile::WaveFile;
+
+extern crate serde_json;
+
+use std::process;
+
+
+struct Arguments {
+  input: String,
+  json:  bool
+}
+
+fn main() {
+  // default arguments.
+  let mut args = Arguments {
+    input: "".into(),
+    json:  false
+  };
+
+  {
+    let mut ap = ArgumentParser::new();
+
+    ap.set_description("Print a summary of a wave file.");
+
+    ap.refer(&mut args.input)
+      .add_argument("input file", Store, "WAV file to read, or - for standard input.")
+      .required();
+    ap.refer(&mut args.json)
+      .add_option(&["--json"], StoreTrue, "print the format information as JSON.");
+
+    ap.parse_args_or_exit();
+  }
+
+  // `open()` treats `-` as standard input.
+  let wav = match WaveFile::open(&args.input) {
+    Ok(f)  => f,
+    Err(e) => {
+      eprintln!("{}", e);
+      process::exit(1);
+    }
+  };
+
+  if args.json {
+    match serde_json::to_string_pretty(&wav.info()) {
+      Ok(json) => println!("{}", json),
+      Err(e)   => {
+        eprintln!("Couldn't serialize {}: {}", &args.input, e);
+        process::exit(1);
+      }
+    }
+  } else {
+    println!("{}", wav.print_summary());
+  }
+}
@@ -0,0 +1,745 @@
+use core::cmp;
+#[cfg(feature = "no_std")]
+use alloc::vec::Vec;
+
+use super::{Format, WaveError, WaveFile};
+
+// `loudness_histogram()` spans `[LOUDNESS_FLOOR, 0]` dBFS in this many bins
+// of equal width; windows quieter than the floor are considered silent.
+#[cfg(not(feature = "no_std"))]
+const LOUDNESS_BINS : usize = 1000;
+#[cfg(not(feature = "no_std"))]
+const LOUDNESS_FLOOR : f32 = -100.0;
+
+/// Summary statistics of the samples in a wavefile, computed in a single
+/// pass.  See `WaveFile::precompute_stats()`.
+#[derive(Debug,Clone,PartialEq)]
+pub struct WaveStats {
+  /// The largest absolute sample value in any channel.
+  pub peak:               f32,
+  /// The root mean square of all samples in all channels.
+  pub rms:                f32,
+  /// The mean of the samples in each channel.  A value noticeably different
+  /// from zero indicates a DC offset in the recording.
+  pub dc_offset:          Vec<f32>,
+  /// The fraction of consecutive samples in each channel which differ in
+  /// sign, averaged across channels.
+  pub zero_crossing_rate: f64
+}
+
+impl WaveFile {
+  /// Scans the file for discontinuities, returning the index of every frame
+  /// where any channel differs from the previous frame by more than
+  /// `threshold`.  Since samples are scaled to [-1, 1], a `threshold` of
+  /// `1.0` corresponds to a jump of half of full scale.
+  ///
+  /// Sudden jumps like these usually indicate a splice artifact or a
+  /// corrupted block of audio.
+  pub fn scan_for_dc_glitches(&self, threshold: f32) -> Vec<usize> {
+    self.iter()
+      .zip(self.iter_from(1).enumerate_frames())
+      .filter(|&(ref previous, (_, ref frame))| {
+        previous.iter().zip(frame.iter()).any(|(a, b)| (b - a).abs() > threshold)
+      })
+      .map(|(_, (index, _))| index)
+      .collect()
+  }
+
+  /// Returns the index of every frame where at least one channel is clipped,
+  /// i.e. reaches full scale: an absolute value of `1.0` or more for float
+  /// data, or the largest or smallest representable value for PCM data.
+  pub fn detect_clipping(&self) -> Vec<usize> {
+    let full_scale = self.full_scale();
+
+    self.iter()
+      .enumerate()
+      .filter(|(_, frame)| frame.iter().any(|&s| s >= full_scale || s <= -1.0))
+      .map(|(index, _)| index)
+      .collect()
+  }
+
+  /// The largest absolute sample value in any channel.
+  ///
+  /// If the file has a `PEAK` chunk, the value is taken from it instead of
+  /// scanning all samples; see `peak_chunk()`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// assert!(wav.peak_sample() <= 1.0);
+  /// ```
+  pub fn peak_sample(&self) -> f32 {
+    if let Some(chunk) = self.peak_chunk() {
+      return chunk.peaks.iter().map(|peak| peak.value.abs()).fold(0.0, f32::max);
+    }
+
+    self.iter()
+      .flat_map(|frame| frame.into_iter())
+      .map(f32::abs)
+      .fold(0.0, f32::max)
+  }
+
+  /// The index of the loudest frame and its level, i.e. the largest
+  /// absolute sample value of any channel in that frame.  If several frames
+  /// are equally loud, the first one is returned.  Returns `(0, 0.0)` for an
+  /// empty file.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav            = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  /// let (index, level) = wav.max_frame();
+  ///
+  /// assert_eq!(level, wav.peak_sample());
+  /// println!("peak at {:?}", wav.elapsed_at(index));
+  /// ```
+  pub fn max_frame(&self) -> (usize, f32) {
+    self.iter()
+      .map(|frame| Self::frame_peak(&frame))
+      .enumerate()
+      .fold((0, 0.0), |max, (index, level)| if level > max.1 { (index, level) } else { max })
+  }
+
+  /// The index of the quietest frame which is not silent, and its level as
+  /// in `max_frame()`.  Frames where all samples are zero are ignored.
+  /// Returns `(0, 0.0)` if the file is empty or entirely silent.
+  pub fn min_frame_nonsilent(&self) -> (usize, f32) {
+    self.iter()
+      .map(|frame| Self::frame_peak(&frame))
+      .enumerate()
+      .filter(|&(_, level)| level > 0.0)
+      .fold(None, |min : Option<(usize, f32)>, (index, level)| match min {
+        Some(min) if min.1 <= level => Some(min),
+        _                           => Some((index, level))
+      })
+      .unwrap_or((0, 0.0))
+  }
+
+  /// The fraction of frames which are clipped, between `0.0` and `1.0`.
+  /// See `detect_clipping()`.
+  pub fn clipping_ratio(&self) -> f64 {
+    if self.is_empty() {
+      return 0.0;
+    }
+
+    self.detect_clipping().len() as f64 / self.len() as f64
+  }
+
+  /// Checks a floating point file for samples which are NaN or infinite,
+  /// since these propagate silently through most processing.  Returns a
+  /// `WaveError::ParseError` describing the first such sample.
+  ///
+  /// Integer PCM data cannot contain invalid samples, so this always
+  /// succeeds for non-float files.
+  pub fn validate_samples(&self) -> Result<(), WaveError> {
+    if !self.is_float() {
+      return Ok(());
+    }
+
+    for (index, frame) in self.iter().enumerate() {
+      for (channel, sample) in frame.iter().enumerate() {
+        let kind = if sample.is_nan() {
+          "NaN"
+        } else if sample.is_infinite() {
+          "Inf"
+        } else {
+          continue;
+        };
+
+        let msg    = format!("{} at frame {}, channel {}", kind, index, channel);
+        let offset = self.data_offset + index as u64 * self.bytes_per_frame()
+                   + channel as u64 * (self.bits_per_sample() / 8) as u64;
+
+        return Err(self.in_file(WaveError::parse_at(msg, offset)));
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Counts the samples which are NaN and infinite respectively, returning
+  /// `(nan, inf)`.  See `validate_samples()`.
+  pub fn count_invalid_samples(&self) -> (usize, usize) {
+    if !self.is_float() {
+      return (0, 0);
+    }
+
+    self.iter().flat_map(|frame| frame.into_iter()).fold((0, 0), |(nan, inf), sample| {
+      (nan + sample.is_nan() as usize, inf + sample.is_infinite() as usize)
+    })
+  }
+
+  /// The Pearson correlation coefficient between the left and right channel
+  /// of a stereo file: `1.0` if the channels are identical (apart from
+  /// their level), `-1.0` if they are in anti-phase and would cancel out
+  /// when mixed to mono.  Returns `0.0` if either channel is constant.
+  ///
+  /// Returns `WaveError::Unsupported` unless the file has two channels.
+  /// Requires `std`.
+  #[cfg(not(feature = "no_std"))]
+  pub fn channel_correlation(&self) -> Result<f32, WaveError> {
+    if !self.is_stereo() {
+      let msg = format!("Channel correlation requires 2 channels, found {}", self.channels());
+      return Err(self.in_file(WaveError::Unsupported(msg)));
+    }
+
+    let (mut sum_l, mut sum_r, mut sum_ll, mut sum_rr, mut sum_lr) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    let mut n = 0.0;
+
+    for frame in self.iter() {
+      let (l, r) = (frame[0] as f64, frame[1] as f64);
+
+      sum_l  += l;
+      sum_r  += r;
+      sum_ll += l * l;
+      sum_rr += r * r;
+      sum_lr += l * r;
+      n      += 1.0;
+    }
+
+    let covariance = n * sum_lr - sum_l * sum_r;
+    let variance_l = n * sum_ll - sum_l * sum_l;
+    let variance_r = n * sum_rr - sum_r * sum_r;
+
+    if variance_l <= 0.0 || variance_r <= 0.0 {
+      return Ok(0.0);
+    }
+
+    Ok((covariance / (variance_l * variance_r).sqrt()) as f32)
+  }
+
+  /// The Pearson correlation coefficient between every pair of channels,
+  /// as a symmetric `channels() × channels()` matrix, e.g. for checking a
+  /// surround mix for channels which are duplicates or in anti-phase.  See
+  /// `channel_correlation()`.  The diagonal is always `1.0`; pairs involving
+  /// a constant channel have a correlation of `0.0`.
+  ///
+  /// Returns `WaveError::Unsupported` for mono files.  Requires `std`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav    = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  /// let matrix = wav.correlation_matrix().unwrap();
+  ///
+  /// assert_eq!(matrix[0][1], matrix[1][0]);
+  /// ```
+  #[cfg(not(feature = "no_std"))]
+  pub fn correlation_matrix(&self) -> Result<Vec<Vec<f32>>, WaveError> {
+    let channels = self.channels() as usize;
+
+    if channels < 2 {
+      let msg = format!("Correlation matrix requires at least 2 channels, found {}", channels);
+      return Err(self.in_file(WaveError::Unsupported(msg)));
+    }
+
+    let mut sums     = vec![0.0f64; channels];
+    let mut products = vec![vec![0.0f64; channels]; channels];
+    let mut n        = 0.0;
+
+    for frame in self.iter() {
+      for i in 0..channels {
+        let a = frame[i] as f64;
+        sums[i] += a;
+
+        for j in i..channels {
+          products[i][j] += a * frame[j] as f64;
+        }
+      }
+
+      n += 1.0;
+    }
+
+    let mut matrix = vec![vec![0.0f32; channels]; channels];
+
+    for i in 0..channels {
+      matrix[i][i] = 1.0;
+
+      for j in i + 1..channels {
+        let covariance = n * products[i][j] - sums[i] * sums[j];
+        let variance_i = n * products[i][i] - sums[i] * sums[i];
+        let variance_j = n * products[j][j] - sums[j] * sums[j];
+
+        if variance_i > 0.0 && variance_j > 0.0 {
+          matrix[i][j] = (covariance / (variance_i * variance_j).sqrt()) as f32;
+          matrix[j][i] = matrix[i][j];
+        }
+      }
+    }
+
+    Ok(matrix)
+  }
+
+  /// Counts the samples of all channels into `bins` buckets of equal width
+  /// spanning `[-1, 1]`, e.g. for plotting how much of the dynamic range a
+  /// recording uses.  Samples outside of that range are counted in the
+  /// first or last bucket.
+  pub fn histogram(&self, bins: usize) -> Vec<u64> {
+    let mut histogram = vec![0; bins];
+
+    for channel in self.histogram_per_channel(bins) {
+      for (total, count) in histogram.iter_mut().zip(channel) {
+        *total += count;
+      }
+    }
+
+    histogram
+  }
+
+  /// Like `histogram()`, but with a separate histogram for each channel.
+  pub fn histogram_per_channel(&self, bins: usize) -> Vec<Vec<u64>> {
+    let mut histograms = vec![vec![0; bins]; self.channels() as usize];
+
+    if bins == 0 {
+      return histograms;
+    }
+
+    for frame in self.iter() {
+      for (histogram, &sample) in histograms.iter_mut().zip(frame.iter()) {
+        let bin = ((sample + 1.0) / 2.0 * bins as f32) as usize;
+        histogram[cmp::min(bin, bins - 1)] += 1;
+      }
+    }
+
+    histograms
+  }
+
+  /// The short-time energy of the file: the sum of squared samples for each
+  /// non-overlapping window of `window_frames` frames, averaged across
+  /// channels.  The last window may be shorter if the length of the file is
+  /// not a multiple of `window_frames`.
+  pub fn energy_windowed(&self, window_frames: usize) -> Vec<f32> {
+    if window_frames == 0 {
+      return Vec::new();
+    }
+
+    let mut energies = Vec::with_capacity(self.len().div_ceil(window_frames));
+    let mut energy : f64 = 0.0;
+
+    for (index, frame) in self.iter().enumerate() {
+      energy += Self::frame_energy(&frame);
+
+      if (index + 1).is_multiple_of(window_frames) {
+        energies.push(energy as f32);
+        energy = 0.0;
+      }
+    }
+
+    if !self.len().is_multiple_of(window_frames) {
+      energies.push(energy as f32);
+    }
+
+    energies
+  }
+
+  /// The total energy of the file, i.e. the sum of squared samples averaged
+  /// across channels.  See `energy_windowed()`.
+  pub fn energy(&self) -> f32 {
+    self.iter().map(|frame| Self::frame_energy(&frame)).sum::<f64>() as f32
+  }
+
+  /// An approximation of the spectral centroid (the "brightness" of the
+  /// sound) in Hz for each non-overlapping window of `window_frames`
+  /// frames, derived from the zero-crossing rate of the channels mixed to
+  /// mono as `ZCR * sample_rate / 2`.  This is exact for a pure tone, but
+  /// only a rough estimate for complex signals; it is however cheap to
+  /// compute and needs no FFT.
+  ///
+  /// The last window may be shorter if the length of the file is not a
+  /// multiple of `window_frames`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// for centroid in wav.spectral_centroid_approx_windowed(4800) {
+  ///   println!("{:.0} Hz", centroid);
+  /// }
+  /// ```
+  pub fn spectral_centroid_approx_windowed(&self, window_frames: usize) -> Vec<f64> {
+    if window_frames == 0 {
+      return Vec::new();
+    }
+
+    let nyquist = self.sample_rate() as f64 / 2.0;
+    let mut centroids = Vec::with_capacity(self.len().div_ceil(window_frames));
+    let mut crossings = 0;
+    let mut previous  = None;
+
+    for (index, frame) in self.iter().enumerate() {
+      let positive = frame.iter().sum::<f32>() >= 0.0;
+
+      if previous.is_some_and(|previous| previous != positive) {
+        crossings += 1;
+      }
+
+      previous = Some(positive);
+
+      if (index + 1).is_multiple_of(window_frames) {
+        centroids.push(crossings as f64 / window_frames as f64 * nyquist);
+        crossings = 0;
+      }
+    }
+
+    let remainder = self.len() % window_frames;
+
+    if remainder != 0 {
+      centroids.push(crossings as f64 / remainder as f64 * nyquist);
+    }
+
+    centroids
+  }
+
+  /// A quick estimate of the file's loudness range in dB: the difference
+  /// between the 95th and the 10th percentile of the RMS levels of its
+  /// 400 ms windows, as found in `loudness_histogram()`.  Silent windows
+  /// are ignored.
+  ///
+  /// This is loosely modelled on the EBU R128 loudness range, but uses
+  /// unweighted, ungated RMS levels, so it is no substitute for a proper
+  /// measurement.  Requires `std`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// println!("Loudness range: {:.1} dB", wav.loudness_range_estimate());
+  /// ```
+  #[cfg(not(feature = "no_std"))]
+  pub fn loudness_range_estimate(&self) -> f32 {
+    let histogram = self.loudness_histogram();
+    let total     = histogram.iter().map(|&(_, count)| count).sum::<u64>();
+
+    if total == 0 {
+      return 0.0;
+    }
+
+    // the lower bound of the bin containing the `p`th percentile.
+    let percentile = |p: f64| {
+      let target = (p * total as f64).ceil() as u64;
+      let mut cumulative = 0;
+
+      histogram.iter().find(|&&(_, count)| {
+        cumulative += count;
+        cumulative >= cmp::max(target, 1)
+      }).map_or(0.0, |&(bound, _)| bound)
+    };
+
+    percentile(0.95) - percentile(0.10)
+  }
+
+  /// The distribution of the RMS levels of the file's non-overlapping
+  /// 400 ms windows, as `(lower_bound, count)` pairs for 1000 bins of
+  /// 0.1 dB between -100 dBFS and 0 dBFS.  Windows above 0 dBFS are counted
+  /// in the last bin; silent windows below -100 dBFS are not counted.  See
+  /// `loudness_range_estimate()`.  Requires `std`.
+  #[cfg(not(feature = "no_std"))]
+  pub fn loudness_histogram(&self) -> Vec<(f32, u64)> {
+    let width = -LOUDNESS_FLOOR / LOUDNESS_BINS as f32;
+    let mut histogram = (0..LOUDNESS_BINS)
+      .map(|bin| (LOUDNESS_FLOOR + bin as f32 * width, 0))
+      .collect::<Vec<_>>();
+
+    let window = cmp::max(self.sample_rate() as usize * 400 / 1000, 1);
+
+    for (index, energy) in self.energy_windowed(window).into_iter().enumerate() {
+      let frames = cmp::min(window, self.len() - index * window);
+      let level  = 10.0 * (energy as f64 / frames as f64).log10() as f32;
+
+      if level.is_nan() || level < LOUDNESS_FLOOR {
+        continue;
+      }
+
+      let bin = ((level - LOUDNESS_FLOOR) / width) as usize;
+      histogram[cmp::min(bin, LOUDNESS_BINS - 1)].1 += 1;
+    }
+
+    histogram
+  }
+
+  /// Computes peak level, RMS level, DC offset and zero-crossing rate of
+  /// the file in a single pass over its samples, which is faster than
+  /// computing each of them separately.  Requires `std`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav   = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  /// let stats = wav.precompute_stats();
+  ///
+  /// assert!(stats.rms <= stats.peak);
+  /// ```
+  #[cfg(not(feature = "no_std"))]
+  pub fn precompute_stats(&self) -> WaveStats {
+    let channels = self.channels() as usize;
+
+    let mut peak : f32    = 0.0;
+    let mut squares : f64 = 0.0;
+    let mut sums          = vec![0.0f64; channels];
+    let mut crossings     = 0u64;
+    let mut previous : Option<Vec<f32>> = None;
+
+    for frame in self.iter() {
+      for (channel, &sample) in frame.iter().enumerate() {
+        peak     = peak.max(sample.abs());
+        squares += sample as f64 * sample as f64;
+        sums[channel] += sample as f64;
+      }
+
+      if let Some(ref previous) = previous {
+        crossings += previous.iter().zip(frame.iter())
+          .filter(|&(&a, &b)| (a >= 0.0) != (b >= 0.0))
+          .count() as u64;
+      }
+
+      previous = Some(frame);
+    }
+
+    let frames  = self.len() as f64;
+    let samples = frames * channels as f64;
+
+    WaveStats {
+      peak,
+      rms:                if samples == 0.0 { 0.0 } else { (squares / samples).sqrt() as f32 },
+      dc_offset:          sums.iter().map(|&sum| if frames == 0.0 { 0.0 } else { (sum / frames) as f32 }).collect(),
+      zero_crossing_rate: if frames < 2.0 { 0.0 } else { crossings as f64 / ((frames - 1.0) * channels as f64) }
+    }
+  }
+
+  fn frame_peak(frame: &[f32]) -> f32 {
+    frame.iter().map(|s| s.abs()).fold(0.0, f32::max)
+  }
+
+  fn frame_energy(frame: &[f32]) -> f64 {
+    let sum = frame.iter().map(|&s| s as f64 * s as f64).sum::<f64>();
+    sum / frame.len() as f64
+  }
+
+  // the largest positive sample value the iterator can yield for this file.
+  fn full_scale(&self) -> f32 {
+    match self.data_format() {
+      Format::PCM => {
+        let scale = (1u64 << ((self.bits_per_sample() / 8) * 8 - 1)) as f32;
+        (scale - 1.0) / scale
+      },
+      _ => 1.0
+    }
+  }
+}
+
+#[cfg(test)]
+use super::{pcm16_wave, sine_wave};
+#[cfg(all(test, not(feature = "no_std")))]
+use super::{build_wave, pcm_fmt};
+
+#[test]
+fn test_scan_for_dc_glitches() {
+  let samples : [i16; 6] = [0, 100, 200, 30000, 30100, -2000];
+  let file = pcm16_wave(1, &samples);
+
+  assert_eq!(file.scan_for_dc_glitches(0.5), [3, 5]);
+  assert_eq!(file.scan_for_dc_glitches(0.95), [5]);
+  assert!(file.scan_for_dc_glitches(2.0).is_empty());
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_detect_clipping() {
+  let samples : [i16; 8] = [0, 32767, 100, -32768, -32767, 32766, 0, 0];
+  let file = pcm16_wave(2, &samples);
+
+  assert_eq!(file.detect_clipping(), [0, 1]);
+  assert_eq!(file.clipping_ratio(), 0.5);
+
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert!(file.detect_clipping().is_empty());
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_validate_samples() {
+  let mut fmt = pcm_fmt(2, 8000, 32);
+  fmt[0] = 3;
+
+  let samples = [0.0, 0.5, f32::NAN, 1.0, f32::INFINITY, f32::NEG_INFINITY, f32::NAN, 0.0];
+  let data = samples.iter().flat_map(|s| s.to_le_bytes().to_vec()).collect::<Vec<_>>();
+  let file = WaveFile::from_vec(build_wave(&fmt, &data)).unwrap();
+
+  match file.validate_samples() {
+    Err(e @ WaveError::ParseError { .. }) => {
+      assert_eq!(e.to_string(), "Parse Error: NaN at frame 1, channel 0 (at byte 52)");
+    },
+    other => panic!("Expected a ParseError, got {:?}", other)
+  }
+  assert_eq!(file.count_invalid_samples(), (2, 2));
+
+  let file = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  assert!(file.validate_samples().is_ok());
+  assert_eq!(file.count_invalid_samples(), (0, 0));
+
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert!(file.validate_samples().is_ok());
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_channel_correlation() {
+  let same     = pcm16_wave(2, &[100, 100, -2000, -2000, 3000, 3000, 0, 0]);
+  let inverted = pcm16_wave(2, &[100, -100, -2000, 2000, 3000, -3000, 0, 0]);
+  let silent   = pcm16_wave(2, &[100, 0, -2000, 0, 3000, 0]);
+
+  assert!((same.channel_correlation().unwrap() - 1.0).abs() < 1e-6);
+  assert!((inverted.channel_correlation().unwrap() + 1.0).abs() < 1e-6);
+  assert_eq!(silent.channel_correlation().unwrap(), 0.0);
+
+  let mono = pcm16_wave(1, &[0; 4]);
+  match mono.channel_correlation() {
+    Err(WaveError::Unsupported(_)) => (),
+    other => panic!("Expected an Unsupported error, got {:?}", other)
+  }
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_correlation_matrix() {
+  let samples : [i16; 12] = [100, 100, -100, 0, -2000, -2000, 2000, 0, 3000, 3000, -3000, 0];
+  let file = pcm16_wave(4, &samples);
+
+  let matrix = file.correlation_matrix().unwrap();
+
+  assert_eq!(matrix.len(), 4);
+  for (i, row) in matrix.iter().enumerate() {
+    assert_eq!(row[i], 1.0);
+    assert_eq!(row[3], if i == 3 { 1.0 } else { 0.0 });
+    assert_eq!(matrix[3][i], row[3]);
+  }
+
+  assert!((matrix[0][1] - 1.0).abs() < 1e-6);
+  assert!((matrix[0][2] + 1.0).abs() < 1e-6);
+  assert_eq!(matrix[2][0], matrix[0][2]);
+
+  let stereo = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  let matrix = stereo.correlation_matrix().unwrap();
+  assert!((matrix[0][1] - stereo.channel_correlation().unwrap()).abs() < 1e-6);
+
+  let mono = pcm16_wave(1, &[0; 4]);
+  match mono.correlation_matrix() {
+    Err(WaveError::Unsupported(_)) => (),
+    other => panic!("Expected an Unsupported error, got {:?}", other)
+  }
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_histogram() {
+  let samples : [i16; 8] = [-32768, -16384, 0, 100, 16384, 32767, -1, 0];
+  let file = pcm16_wave(2, &samples);
+
+  assert_eq!(file.histogram(4), [1, 2, 3, 2]);
+  assert_eq!(file.histogram_per_channel(4), [[1, 1, 1, 1], [0, 1, 2, 1]]);
+  assert!(file.histogram(0).is_empty());
+
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert_eq!(file.histogram(64).iter().sum::<u64>(), file.total_samples());
+}
+
+#[test]
+fn test_energy() {
+  let samples : [i16; 10] = [16384, 16384, -16384, 0, 0, 0, 8192, -8192, 16384, -16384];
+  let file = pcm16_wave(2, &samples);
+
+  assert_eq!(file.energy_windowed(2), [0.375, 0.0625, 0.25]);
+  assert_eq!(file.energy_windowed(5), [0.6875]);
+  assert!(file.energy_windowed(0).is_empty());
+  assert_eq!(file.energy(), 0.6875);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_loudness_range_estimate() {
+  // 400 ms windows at 1000 Hz are 400 frames long; alternate between
+  // windows at -6 dBFS, -26 dBFS and silence.
+  let levels : [i16; 6] = [16384, 1638, 0, 16384, 1638, 16384];
+  let data = levels.iter().flat_map(|&level| {
+    (0..400).flat_map(move |i| (if i % 2 == 0 { level } else { -level }).to_le_bytes().to_vec())
+  }).collect::<Vec<_>>();
+  let file = WaveFile::from_vec(build_wave(&pcm_fmt(1, 1000, 16), &data)).unwrap();
+
+  let histogram = file.loudness_histogram();
+  let counted   = histogram.iter().filter(|&&(_, count)| count > 0).collect::<Vec<_>>();
+
+  assert_eq!(histogram.len(), 1000);
+  assert_eq!(histogram[0].0, -100.0);
+  assert_eq!(counted.len(), 2);
+  assert!((counted[0].0 + 26.0).abs() < 0.2 && counted[0].1 == 2, "{:?}", counted);
+  assert!((counted[1].0 + 6.0).abs() < 0.2 && counted[1].1 == 3, "{:?}", counted);
+
+  assert!((file.loudness_range_estimate() - 20.0).abs() < 0.2);
+
+  let silent = WaveFile::from_vec(build_wave(&pcm_fmt(1, 1000, 16), &[0; 800])).unwrap();
+  assert_eq!(silent.loudness_range_estimate(), 0.0);
+}
+
+#[test]
+fn test_max_and_min_frame() {
+  let samples : [i16; 10] = [0, 0, 4096, -8192, -16384, 0, 0, 2048, 16384, 0];
+  let file = pcm16_wave(2, &samples);
+
+  assert_eq!(file.max_frame(), (2, 0.5));
+  assert_eq!(file.min_frame_nonsilent(), (3, 0.0625));
+
+  let silent = pcm16_wave(1, &[0; 4]);
+  assert_eq!(silent.max_frame(), (0, 0.0));
+  assert_eq!(silent.min_frame_nonsilent(), (0, 0.0));
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_precompute_stats() {
+  let samples : [i16; 8] = [16384, 8192, -16384, 8192, 16384, 8192, -16384, -32768];
+  let file = pcm16_wave(2, &samples);
+
+  let stats = file.precompute_stats();
+
+  assert_eq!(stats.peak, 1.0);
+  assert!((stats.rms - (2.1875f32 / 8.0).sqrt()).abs() < 1e-6);
+  assert_eq!(stats.dc_offset, [0.0, -0.0625]);
+  assert_eq!(stats.zero_crossing_rate, 4.0 / 6.0);
+
+  let empty = pcm16_wave(1, &[]);
+  assert_eq!(empty.precompute_stats(), WaveStats {
+    peak: 0.0, rms: 0.0, dc_offset: vec![0.0], zero_crossing_rate: 0.0
+  });
+}
+
+#[test]
+fn test_spectral_centroid_approx_windowed() {
+  let low       = sine_wave(8000, 500.0, 1600).spectral_centroid_approx_windowed(800);
+  let high      = sine_wave(8000, 2000.0, 1600).spectral_centroid_approx_windowed(800);
+  let remainder = sine_wave(8000, 1000.0, 1000).spectral_centroid_approx_windowed(800);
+
+  assert_eq!(low.len(), 2);
+  assert!(low.iter().all(|&hz| (hz - 500.0).abs() < 10.0), "{:?}", low);
+  assert!(high.iter().all(|&hz| (hz - 2000.0).abs() < 10.0), "{:?}", high);
+  assert_eq!(remainder.len(), 2);
+  assert!((remainder[1] - 1000.0).abs() < 10.0, "{:?}", remainder);
+
+  assert!(sine_wave(8000, 0.0, 100).spectral_centroid_approx_windowed(10).iter().all(|&hz| hz == 0.0));
+  assert!(sine_wave(8000, 500.0, 100).spectral_centroid_approx_windowed(0).is_empty());
+}
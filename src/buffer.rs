@@ -0,0 +1,216 @@
+#[cfg(feature = "no_std")]
+use alloc::vec::Vec;
+
+use super::{Format, Frame, WaveError, WaveFile};
+
+/// Audio samples held in memory with one `Vec` per channel ("planar"
+/// layout), as opposed to the interleaved frames of a wavefile.  This is the
+/// layout most signal processing code expects.  See
+/// `WaveFile::to_audio_buffer()`.
+#[derive(Debug,Clone,PartialEq)]
+pub struct AudioBuffer {
+  /// The samples of each channel, all of the same length.
+  pub channels:    Vec<Vec<f32>>,
+  /// Number of samples per second in each channel.
+  pub sample_rate: u32
+}
+
+/// An iterator which yields the samples of an `AudioBuffer` interleaved into
+/// `Frames`.  See `AudioBuffer::iter_frames()`.
+pub struct AudioBufferFrames<'a> {
+  buffer: &'a AudioBuffer,
+  index:  usize
+}
+
+impl WaveFile {
+  /// Decodes the entire file into an `AudioBuffer`, with the samples of
+  /// each channel stored separately.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav    = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  /// let buffer = wav.to_audio_buffer();
+  ///
+  /// assert_eq!(buffer.channels.len(), 2);
+  /// assert_eq!(buffer.len(), wav.len());
+  /// ```
+  pub fn to_audio_buffer(&self) -> AudioBuffer {
+    let mut channels = vec![Vec::with_capacity(self.len()); self.channels() as usize];
+
+    for frame in self.iter() {
+      for (channel, sample) in channels.iter_mut().zip(frame) {
+        channel.push(sample);
+      }
+    }
+
+    AudioBuffer { channels, sample_rate: self.sample_rate() }
+  }
+
+  /// Decodes the entire file into interleaved 16-bit PCM samples, as
+  /// accepted by most audio hardware APIs.  8 and 16-bit PCM are converted
+  /// losslessly; other formats are rounded to the nearest 16-bit value and
+  /// clamped to its range.
+  ///
+  /// If `dither` is `true`, triangular dither of one least significant bit
+  /// is added before rounding, which turns quantization distortion into
+  /// low level noise.  Dither is only applied when precision is actually
+  /// lost, i.e. not to 8 or 16-bit PCM input.
+  ///
+  /// Returns `WaveError::Unsupported` if the samples of this file can't be
+  /// decoded.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav     = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  /// let samples = wav.decode_to_pcm16(true).unwrap();
+  ///
+  /// assert_eq!(samples.len(), wav.len() * wav.channels() as usize);
+  /// ```
+  pub fn decode_to_pcm16(&self, dither: bool) -> Result<Vec<i16>, WaveError> {
+    let bps = self.bits_per_sample();
+
+    let lossless = match (self.data_format(), bps) {
+      (Format::PCM, 8) | (Format::PCM, 16)              => true,
+      (Format::PCM, 24) | (Format::PCM, 32)             => false,
+      (Format::IEEEFloat, 32) | (Format::IEEEFloat, 64) => false,
+      (format, _)                                       => {
+        let msg = format!("Cannot decode {} bit {} data", bps, format);
+        return Err(WaveError::Unsupported(msg));
+      }
+    };
+
+    let mut noise   = Dither::new();
+    let mut samples = Vec::with_capacity(self.total_samples() as usize);
+
+    for sample in self.iter().flatten() {
+      let mut value = sample * 32768.0;
+
+      if dither && !lossless {
+        value += noise.next();
+      }
+
+      // `f32::round()` is not available without `std`.
+      let rounded = if value >= 0.0 { value + 0.5 } else { value - 0.5 };
+      samples.push(rounded.clamp(-32768.0, 32767.0) as i16);
+    }
+
+    Ok(samples)
+  }
+}
+
+// generates triangular dither noise between -1 and 1, from the sum of two
+// uniformly distributed values.  A fixed seed keeps the output reproducible.
+struct Dither {
+  state: u32
+}
+
+impl Dither {
+  fn new() -> Dither {
+    Dither { state: 0x9e3779b9 }
+  }
+
+  // xorshift32, which is plenty for noise that only needs to be
+  // uncorrelated with the signal.
+  fn uniform(&mut self) -> f32 {
+    self.state ^= self.state << 13;
+    self.state ^= self.state >> 17;
+    self.state ^= self.state << 5;
+
+    (self.state >> 8) as f32 / (1 << 24) as f32
+  }
+
+  fn next(&mut self) -> f32 {
+    self.uniform() + self.uniform() - 1.0
+  }
+}
+
+impl AudioBuffer {
+  /// The number of samples in each channel.
+  pub fn len(&self) -> usize {
+    self.channels.first().map_or(0, |channel| channel.len())
+  }
+
+  /// Returns `true` if the buffer contains no samples.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Returns an iterator which yields the samples of all channels
+  /// interleaved into `Frames`, the inverse of `WaveFile::to_audio_buffer()`.
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn iter_frames(&self) -> AudioBufferFrames<'_> {
+    AudioBufferFrames { buffer: self, index: 0 }
+  }
+}
+
+impl<'a> Iterator for AudioBufferFrames<'a> {
+  type Item = Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.index >= self.buffer.len() {
+      return None;
+    }
+
+    let frame = self.buffer.channels.iter().map(|channel| channel[self.index]).collect();
+    self.index += 1;
+
+    Some(frame)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let remaining = self.buffer.len().saturating_sub(self.index);
+    (remaining, Some(remaining))
+  }
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_audio_buffer() {
+  let file   = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+  let buffer = file.to_audio_buffer();
+
+  assert_eq!(buffer.sample_rate, 8000);
+  assert_eq!(buffer.channels.len(), 2);
+  assert_eq!(buffer.len(), file.len());
+  assert_eq!(buffer.channels[1][3], file.at(3).unwrap()[1]);
+
+  let frames = buffer.iter_frames();
+  assert_eq!(frames.size_hint(), (file.len(), Some(file.len())));
+  assert_eq!(frames.collect::<Vec<_>>(), file.iter().collect::<Vec<_>>());
+
+  let empty = AudioBuffer { channels: Vec::new(), sample_rate: 8000 };
+  assert!(empty.is_empty());
+  assert_eq!(empty.iter_frames().count(), 0);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_decode_to_pcm16() {
+  use super::pcm16_wave;
+
+  let samples : [i16; 4] = [0, -32768, 32767, 1234];
+  let file = pcm16_wave(2, &samples);
+
+  assert_eq!(file.decode_to_pcm16(false).unwrap(), samples);
+  assert_eq!(file.decode_to_pcm16(true).unwrap(), samples);
+
+  let file = WaveFile::open("./fixtures/test-u8.wav").unwrap();
+  let pcm  = file.decode_to_pcm16(true).unwrap();
+  assert!(pcm.iter().zip(file.iter().flatten()).all(|(&a, b)| a as f32 == b * 32768.0));
+
+  let file      = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  let plain     = file.decode_to_pcm16(false).unwrap();
+  let dithered  = file.decode_to_pcm16(true).unwrap();
+  let float_max = file.iter().flatten().map(|s| (s * 32768.0).abs()).fold(0.0, f32::max);
+
+  assert_eq!(plain.len(), file.len() * 2);
+  assert!(plain.iter().zip(dithered.iter()).all(|(&a, &b)| (a as i32 - b as i32).abs() <= 1));
+  assert!(plain.iter().zip(dithered.iter()).any(|(a, b)| a != b));
+  assert!(plain.iter().all(|&s| (s as f32).abs() <= float_max + 0.5));
+}
@@ -0,0 +1,266 @@
+use core::cmp;
+#[cfg(not(feature = "no_std"))]
+use std::io::Write;
+use byteorder::{ByteOrder, BigEndian, LittleEndian};
+
+use super::{WaveFile, DATA, DS64};
+#[cfg(not(feature = "no_std"))]
+use super::WaveError;
+
+/// A single top-level chunk of a wavefile, as yielded by
+/// `WaveFile::chunks_iter()`.
+#[derive(Debug,Copy,Clone)]
+pub struct Chunk<'a> {
+  /// The chunk's FourCC, read as a little-endian `u32`; e.g. `fmt ` is
+  /// `0x20746d66`.  In Wave64 files this is the first four bytes of the
+  /// chunk's GUID, which match the FourCC for all standard chunks.
+  pub id:     u32,
+  /// The size of the chunk's data, as declared in the chunk header.
+  pub size:   u64,
+  /// The offset of the chunk's data from the start of the file.
+  pub offset: u64,
+  /// The chunk's data, not including the chunk header.  This may be
+  /// shorter than `size` if the file is truncated.
+  pub data:   &'a [u8]
+}
+
+/// An iterator over the top-level chunks of a wavefile.
+/// See `WaveFile::chunks_iter()`.
+pub struct ChunkIterator<'a> {
+  bytes:          &'a [u8],
+  pos:            u64,
+  big_endian:     bool,
+  wave64:         bool,
+  skip_nulls:     bool,
+  ds64_data_size: Option<u64>
+}
+
+impl WaveFile {
+  /// Returns an iterator over all top-level chunks in the file (following
+  /// the `RIFF` header), including `data` and any chunks this crate does not
+  /// know about.  Chunk contents are not interpreted.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  ///
+  /// for chunk in wav.chunks_iter() {
+  ///   println!("{:x}: {} bytes", chunk.id, chunk.size);
+  /// }
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn chunks_iter(&self) -> ChunkIterator<'_> {
+    ChunkIterator::new(&self.buffer, self.big_endian, self.wave64)
+      .skip_null_padding(self.lenient)
+  }
+
+  /// Finds the first chunk with the given id and writes it unmodified,
+  /// including its header and any padding, to `writer`.  Returns
+  /// `Ok(false)` if the file contains no such chunk.
+  ///
+  /// This allows chunks like `bext` or `iXML` to be carried over to another
+  /// wavefile without decoding them.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  /// let mut out = Vec::new();
+  ///
+  /// assert!(wav.copy_chunk_to(0x5453494c, &mut out).unwrap());
+  /// assert_eq!(&out[..4], b"LIST");
+  /// ```
+  #[cfg(not(feature = "no_std"))]
+  pub fn copy_chunk_to<W: Write>(&self, chunk_id: u32, writer: &mut W) -> Result<bool, WaveError> {
+    let chunk = match self.chunks_iter().find(|chunk| chunk.id == chunk_id) {
+      Some(chunk) => chunk,
+      None        => return Ok(false)
+    };
+
+    let (header, align) = if self.wave64 { (24, 8) } else { (8, 2) };
+    let start = chunk.offset as usize - header;
+
+    if chunk.data.len() < chunk.size as usize {
+      let msg = format!("Chunk {:x} is truncated", chunk.id);
+      return Err(self.in_file(WaveError::parse_at(msg, start as u64)));
+    }
+
+    let end   = chunk.offset as usize + chunk.size as usize;
+    let pad   = (align - chunk.size as usize % align) % align;
+
+    writer.write_all(&self.buffer[start..end])?;
+    writer.write_all(&[0; 8][..pad])?;
+
+    Ok(true)
+  }
+}
+
+impl<'a> ChunkIterator<'a> {
+  pub(crate) fn new(bytes: &'a [u8], big_endian: bool, wave64: bool) -> ChunkIterator<'a> {
+    let pos = if wave64 { 40 } else { 12 };
+    ChunkIterator { bytes, pos, big_endian, wave64, skip_nulls: false, ds64_data_size: None }
+  }
+
+  // iterates over the sub-chunks in the data of a `LIST` chunk, following
+  // its four byte list type.  Offsets are relative to `data`.
+  pub(crate) fn list(data: &'a [u8], big_endian: bool) -> ChunkIterator<'a> {
+    ChunkIterator { bytes: data, pos: 4, big_endian, wave64: false, skip_nulls: false, ds64_data_size: None }
+  }
+
+  // some writers leave null bytes between chunks which are not accounted
+  // for in the preceding chunk's size.  When enabled, up to 256 of them are
+  // skipped before reading the next chunk header.
+  pub(crate) fn skip_null_padding(mut self, skip_nulls: bool) -> ChunkIterator<'a> {
+    self.skip_nulls = skip_nulls;
+    self
+  }
+
+  fn skip_nulls(&mut self) {
+    let start = self.pos as usize;
+
+    if self.bytes.len() < start + 4 || self.bytes[start..start + 4] != [0; 4] {
+      return;
+    }
+
+    let limit = cmp::min(start + 256, self.bytes.len());
+
+    if let Some(skipped) = self.bytes[start..limit].iter().position(|&byte| byte != 0) {
+      self.pos += skipped as u64;
+    }
+  }
+
+  // Wave64 chunk headers are a 16 byte GUID followed by a 64-bit size which
+  // includes the header itself; chunks are padded to a multiple of 8 bytes.
+  fn next_wave64(&mut self) -> Option<Chunk<'a>> {
+    let len = self.bytes.len() as u64;
+
+    if self.pos + 24 > len {
+      return None;
+    }
+
+    let header = &self.bytes[self.pos as usize..self.pos as usize + 24];
+    let id     = LittleEndian::read_u32(&header[0..4]);
+    let size   = LittleEndian::read_u64(&header[16..24]).saturating_sub(24);
+
+    let offset = self.pos + 24;
+    let end    = cmp::min(offset.saturating_add(size), len);
+
+    // a chunk which runs past the end of the file is the last one.
+    self.pos = if end < len { (end + 7) & !7 } else { len };
+
+    Some(Chunk { id, size, offset, data: &self.bytes[offset as usize..end as usize] })
+  }
+}
+
+impl<'a> Iterator for ChunkIterator<'a> {
+  type Item = Chunk<'a>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.wave64 {
+      return self.next_wave64();
+    }
+
+    if self.skip_nulls {
+      self.skip_nulls();
+    }
+
+    let len = self.bytes.len() as u64;
+
+    if self.pos + 8 > len {
+      return None;
+    }
+
+    let header = &self.bytes[self.pos as usize..self.pos as usize + 8];
+    let id     = LittleEndian::read_u32(&header[0..4]);
+    let size   = if self.big_endian {
+      BigEndian::read_u32(&header[4..8])
+    } else {
+      LittleEndian::read_u32(&header[4..8])
+    };
+
+    // in RF64 files, a `data` chunk too large for a 32-bit size has its
+    // size stored in the preceding `ds64` chunk instead.
+    let size = match self.ds64_data_size {
+      Some(data_size) if id == DATA && size == u32::MAX => data_size,
+      _                                                 => size as u64
+    };
+
+    let offset = self.pos + 8;
+    let end    = cmp::min(offset.saturating_add(size), len);
+
+    // chunks are padded to an even number of bytes, and a chunk which runs
+    // past the end of the file is the last one.
+    self.pos = if end < len { end + (size & 1) } else { len };
+
+    let data = &self.bytes[offset as usize..end as usize];
+
+    if id == DS64 && data.len() >= 16 {
+      self.ds64_data_size = Some(LittleEndian::read_u64(&data[8..16]));
+    }
+
+    Some(Chunk { id, size, offset, data })
+  }
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_chunks_iter() {
+  let file   = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  let chunks = file.chunks_iter().collect::<Vec<_>>();
+  let ids    = chunks.iter().map(|chunk| chunk.id).collect::<Vec<_>>();
+
+  assert_eq!(ids, [0x20746d66, 0x74636166, 0x5453494c, 0x61746164]);
+  assert_eq!(chunks[0].size, 40);
+  assert_eq!(chunks[0].offset, 20);
+  assert_eq!(chunks[1].data, [0x80, 0xa8, 0x07, 0x00]);
+  assert_eq!(chunks[3].data.len(), file.len() * 8);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_copy_chunk_to() {
+  let file = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  let mut out = Vec::new();
+
+  assert!(file.copy_chunk_to(0x74636166, &mut out).unwrap());
+  assert_eq!(out, b"fact\x04\0\0\0\x80\xa8\x07\0");
+  assert!(!file.copy_chunk_to(0x74786562, &mut out).unwrap());
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_chunks_iter_wave64() {
+  let file   = WaveFile::open("./fixtures/test-s16le.w64").unwrap();
+  let chunks = file.chunks_iter().collect::<Vec<_>>();
+  let ids    = chunks.iter().map(|chunk| chunk.id).collect::<Vec<_>>();
+
+  assert_eq!(ids, [0x20746d66, 0x61746164]);
+  assert_eq!(chunks[0].size, 18);
+  assert_eq!(chunks[1].offset, 112);
+
+  let mut out = Vec::new();
+  assert!(file.copy_chunk_to(0x20746d66, &mut out).unwrap());
+  assert_eq!(out.len(), 48);
+  assert_eq!(&out[..], &file.header_bytes()[40..88]);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_chunks_iter_wave64_oversized() {
+  let mut bytes = std::fs::read("./fixtures/test-s16le.w64").unwrap();
+  bytes.extend_from_slice(b"junk\xf3\xac\xd3\x11\x8c\xd1\x00\xc0\x4f\x8e\xdb\x8a");
+  bytes.extend_from_slice(&u64::MAX.to_le_bytes());
+
+  let file   = WaveFile::from_vec(bytes).unwrap();
+  let chunks = file.chunks_iter().collect::<Vec<_>>();
+
+  assert_eq!(chunks.len(), 3);
+  assert_eq!(chunks[2].id, 0x6b6e756a);
+  assert_eq!(chunks[2].size, u64::MAX - 24);
+  assert!(chunks[2].data.is_empty());
+}
@@ -0,0 +1,104 @@
+use core::cmp;
+#[cfg(feature = "no_std")]
+use alloc::string::String;
+
+use super::{WaveError, WaveFile};
+
+/// The outcome of comparing two wavefiles.  See `WaveFile::compare()`.
+#[derive(Debug,Clone,PartialEq)]
+pub enum CompareResult {
+  /// The files are byte for byte identical.
+  Identical,
+  /// The decoded audio is identical, but the files differ elsewhere, e.g. in
+  /// their metadata chunks.
+  SameAudioDifferentMetadata,
+  /// The files cannot be compared sample by sample; the message describes
+  /// the first mismatch in sample rate, channels, bits per sample or format.
+  FormatMismatch(String),
+  /// The decoded audio differs.  `max_diff` is the largest absolute
+  /// difference between two corresponding samples, and `first_diff_frame`
+  /// the index of the first frame which differs.
+  AudioDiffers { max_diff: f32, first_diff_frame: usize }
+}
+
+impl WaveFile {
+  /// Compares the audio in this file with the audio in `other`, e.g. for
+  /// regression testing an audio processing tool.  All frames are compared
+  /// in a single pass; if one file is shorter than the other, its missing
+  /// frames are compared as silence.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::{WaveFile,CompareResult};
+  ///
+  /// let a = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  /// let b = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// assert_eq!(a.compare(&b), CompareResult::Identical);
+  /// ```
+  pub fn compare(&self, other: &WaveFile) -> CompareResult {
+    if let Some(WaveError::Incompatible(msg)) = self.info.compatibility_error(&other.info) {
+      return CompareResult::FormatMismatch(msg);
+    }
+
+    let silence = vec![0.0; self.channels() as usize];
+    let mut ours   = self.iter();
+    let mut theirs = other.iter();
+
+    let mut max_diff : f32 = 0.0;
+    let mut first_diff_frame = None;
+
+    for index in 0.. {
+      let (a, b) = match (ours.next(), theirs.next()) {
+        (None, None) => break,
+        (a, b)       => (a.unwrap_or_else(|| silence.clone()), b.unwrap_or_else(|| silence.clone()))
+      };
+
+      for (x, y) in a.iter().zip(b.iter()) {
+        if x != y {
+          max_diff = max_diff.max((x - y).abs());
+          first_diff_frame = Some(cmp::min(index, first_diff_frame.unwrap_or(index)));
+        }
+      }
+    }
+
+    match first_diff_frame {
+      Some(first_diff_frame)                => CompareResult::AudioDiffers { max_diff, first_diff_frame },
+      None if *self.buffer == *other.buffer => CompareResult::Identical,
+      None                                  => CompareResult::SameAudioDifferentMetadata
+    }
+  }
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_compare() {
+  use std::fs;
+
+  let original = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+  let bytes    = fs::read("./fixtures/test-s16be-rifx.wav").unwrap();
+
+  assert_eq!(original.compare(&original.clone_into_memory()), CompareResult::Identical);
+
+  // same audio, with an extra (empty) LIST chunk before the data chunk.
+  let mut tagged = bytes.clone();
+  tagged.splice(0x24..0x24, b"LIST\0\0\0\x04INFO".iter().cloned());
+  tagged[7] += 12;
+  let tagged = WaveFile::from_vec(tagged).unwrap();
+
+  assert_eq!(original.compare(&tagged), CompareResult::SameAudioDifferentMetadata);
+
+  // frame 3, right channel: -3072 becomes -2048.
+  let mut altered = bytes.clone();
+  altered[44 + 3 * 4 + 2] = 0xf8;
+  let altered = WaveFile::from_vec(altered).unwrap();
+
+  assert_eq!(original.compare(&altered),
+             CompareResult::AudioDiffers { max_diff: 0.03125, first_diff_frame: 3 });
+
+  match original.compare(&WaveFile::open("./fixtures/test-s24le.wav").unwrap()) {
+    CompareResult::FormatMismatch(_) => (),
+    other => panic!("Expected a FormatMismatch, got {:?}", other)
+  }
+}
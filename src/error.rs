@@ -1,41 +1,187 @@
+use core::fmt::{self,Display};
+#[cfg(feature = "no_std")]
+use alloc::string::String;
+#[cfg(not(feature = "no_std"))]
 use std::io;
-use std::fmt::{self,Display};
+#[cfg(not(feature = "no_std"))]
 use std::error::Error;
+#[cfg(not(feature = "no_std"))]
+use std::path::{Path,PathBuf};
 
 #[derive(Debug)]
 pub enum WaveError {
+  #[cfg(not(feature = "no_std"))]
   IoError(io::Error),
   Unsupported(String),
-  ParseError(String)
+  /// The file is malformed.  `byte_offset` is the position in the file
+  /// where the problem was found, if it can be attributed to one.
+  ParseError { message: String, byte_offset: Option<u64> },
+  Incompatible(String),
+  /// A frame index or position lies beyond the end of the file.
+  OutOfRange(String),
+  InconsistentHeader(String),
+  /// The data does not start with a `RIFF`/`WAVE` header, i.e. this is not a
+  /// wavefile at all (but possibly an AIFF, MP3, ... file).
+  NotAWavFile,
+  /// Wraps an error that occurred while opening the file at the given path.
+  #[cfg(not(feature = "no_std"))]
+  InFile(PathBuf, Box<WaveError>)
 }
 
+impl WaveError {
+  pub(crate) fn parse<S: Into<String>>(message: S) -> WaveError {
+    WaveError::ParseError { message: message.into(), byte_offset: None }
+  }
+
+  pub(crate) fn parse_at<S: Into<String>>(message: S, byte_offset: u64) -> WaveError {
+    WaveError::ParseError { message: message.into(), byte_offset: Some(byte_offset) }
+  }
 
+  // attaches `offset` to a `ParseError` raised by code which only sees part
+  // of the file, e.g. a single chunk, and therefore doesn't know it.
+  pub(crate) fn at_offset(self, offset: u64) -> WaveError {
+    match self {
+      WaveError::ParseError { message, byte_offset: None } => WaveError::parse_at(message, offset),
+      e                                                    => e
+    }
+  }
+
+  /// Attaches the path of the file this error occurred in, so that it is
+  /// included when the error is displayed.
+  #[cfg(not(feature = "no_std"))]
+  pub fn with_path<P: AsRef<Path>>(self, path: P) -> WaveError {
+    let path = path.as_ref().to_path_buf();
+
+    match self {
+      WaveError::InFile(_, e) => WaveError::InFile(path, e),
+      e                       => WaveError::InFile(path, Box::new(e))
+    }
+  }
+
+  /// The path of the file this error occurred in, if known.
+  #[cfg(not(feature = "no_std"))]
+  pub fn path(&self) -> Option<&Path> {
+    match *self {
+      WaveError::InFile(ref path, _) => Some(path),
+      _                              => None
+    }
+  }
+
+  /// The position in the file at which parsing failed, if known.  Useful
+  /// for locating the problem in a hex editor.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// if let Err(e) = WaveFile::open("./fixtures/test-s24le.wav") {
+  ///   if let Some(offset) = e.byte_offset() {
+  ///     println!("{} (at {:#x})", e, offset);
+  ///   }
+  /// }
+  /// ```
+  pub fn byte_offset(&self) -> Option<u64> {
+    match *self.inner() {
+      WaveError::ParseError { byte_offset, .. } => byte_offset,
+      _                                         => None
+    }
+  }
+
+  /// The underlying error, without any file path attached.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::{WaveFile,WaveError};
+  ///
+  /// if let Err(e) = WaveFile::open("./Cargo.toml") {
+  ///   if let WaveError::NotAWavFile = *e.inner() {
+  ///     println!("skipping {:?}", e.path());
+  ///   }
+  /// }
+  /// ```
+  pub fn inner(&self) -> &WaveError {
+    match *self {
+      #[cfg(not(feature = "no_std"))]
+      WaveError::InFile(_, ref e) => e.inner(),
+      ref e                       => e
+    }
+  }
+
+  #[cfg(not(feature = "no_std"))]
+  fn io_kind(&self) -> io::ErrorKind {
+    match *self {
+      WaveError::IoError(ref e)        => e.kind(),
+      WaveError::ParseError { .. }     |
+      WaveError::InconsistentHeader(_) |
+      WaveError::NotAWavFile           => io::ErrorKind::InvalidData,
+      WaveError::Unsupported(_)        => io::ErrorKind::Unsupported,
+      WaveError::Incompatible(_)       |
+      WaveError::OutOfRange(_)         => io::ErrorKind::InvalidInput,
+      WaveError::InFile(_, ref e)      => e.io_kind()
+    }
+  }
+
+  fn fmt_in(&self, f: &mut fmt::Formatter, location: &str) -> fmt::Result {
+    match *self {
+      #[cfg(not(feature = "no_std"))]
+      WaveError::IoError(ref e)            => write!(f, "IO Error{}: {}", location, e),
+      WaveError::ParseError { ref message, byte_offset: None } =>
+        write!(f, "Parse Error{}: {}", location, message),
+      WaveError::ParseError { ref message, byte_offset: Some(offset) } =>
+        write!(f, "Parse Error{}: {} (at byte {})", location, message, offset),
+      WaveError::Unsupported(ref s)        => write!(f, "Unsupported Format Error{}: {}", location, s),
+      WaveError::Incompatible(ref s)       => write!(f, "Incompatible Format Error{}: {}", location, s),
+      WaveError::OutOfRange(ref s)         => write!(f, "Out of Range Error{}: {}", location, s),
+      WaveError::InconsistentHeader(ref s) => write!(f, "Inconsistent Header Error{}: {}", location, s),
+      WaveError::NotAWavFile               => write!(f, "Not a WAV file{}", location),
+      #[cfg(not(feature = "no_std"))]
+      WaveError::InFile(_, ref e)          => e.fmt_in(f, location)
+    }
+  }
+}
+
+#[cfg(not(feature = "no_std"))]
 impl From<io::Error> for WaveError {
   fn from(e: io::Error) -> Self {
     WaveError::IoError(e)
   }
 }
 
-impl Error for WaveError {
-  fn description(&self) -> &str {
-    match *self {
-      WaveError::ParseError(ref s)  |
-      WaveError::Unsupported(ref s) => &s,
-      WaveError::IoError(ref e)     => e.description()
+#[cfg(not(feature = "no_std"))]
+impl From<WaveError> for io::Error {
+  fn from(e: WaveError) -> Self {
+    let kind = e.io_kind();
+
+    match e {
+      WaveError::IoError(e)            => e,
+      WaveError::Unsupported(s)        |
+      WaveError::Incompatible(s)       |
+      WaveError::OutOfRange(s)         |
+      WaveError::InconsistentHeader(s) => io::Error::new(kind, s),
+      e                                => io::Error::new(kind, e.to_string())
     }
   }
+}
 
-  fn cause(&self) -> Option<&Error> {
-    if let WaveError::IoError(ref e) = *self { Some(e) } else { None }
+#[cfg(not(feature = "no_std"))]
+impl Error for WaveError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match *self {
+      WaveError::IoError(ref e)   => Some(e),
+      WaveError::InFile(_, ref e) => e.source(),
+      _                           => None
+    }
   }
 }
 
 impl Display for WaveError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    match self {
-      &WaveError::IoError(ref e)     => write!(f, "IO Error: {}", e),
-      &WaveError::ParseError(ref s)  => write!(f, "Parse Error: {}", s),
-      &WaveError::Unsupported(ref s) => write!(f, "Unsupported Format Error: {}", s)
+    match *self {
+      #[cfg(not(feature = "no_std"))]
+      WaveError::InFile(ref path, ref e) => e.fmt_in(f, &format!(" in '{}'", path.display())),
+      _                                  => self.fmt_in(f, "")
     }
   }
 }
@@ -1,21 +1,82 @@
+use core::fmt::{self,Display};
+
 const FORMAT_PCM  : u16 = 1;
 const FORMAT_IEEE : u16 = 3;
 const FORMAT_EXT  : u16 = 0xfffe;
 
 #[derive(Debug,Copy,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Format {
-  PCM        = FORMAT_PCM  as isize,
-  IEEEFloat  = FORMAT_IEEE as isize,
-  Extensible = FORMAT_EXT  as isize
+  PCM,
+  IEEEFloat,
+  Extensible,
+  /// A format code this crate does not know how to decode.
+  /// The raw value from the header is preserved.
+  Unknown(u16)
 }
 
 impl Format {
-  pub fn decode(val: u16) -> Option<Format> {
+  pub fn decode(val: u16) -> Format {
     match val {
-      FORMAT_PCM  => Some(Format::PCM),
-      FORMAT_IEEE => Some(Format::IEEEFloat),
-      FORMAT_EXT  => Some(Format::Extensible),
-      _           => None
+      FORMAT_PCM  => Format::PCM,
+      FORMAT_IEEE => Format::IEEEFloat,
+      FORMAT_EXT  => Format::Extensible,
+      other       => Format::Unknown(other)
+    }
+  }
+
+  /// A human readable name for this format, e.g. `"IEEE Float"`.
+  /// All unknown formats are named `"Unknown"`; see `Display` for a
+  /// representation which includes the format code.
+  pub fn name(&self) -> &'static str {
+    match *self {
+      Format::PCM        => "PCM",
+      Format::IEEEFloat  => "IEEE Float",
+      Format::Extensible => "Extensible",
+      Format::Unknown(_) => "Unknown"
+    }
+  }
+
+  /// The MIME type of files using this format.  This is always
+  /// `"audio/wav"`, since the container does not depend on the format.
+  pub fn mime_type(&self) -> &'static str {
+    "audio/wav"
+  }
+
+  /// The name of the codec used to encode samples in this format.
+  /// Some common compressed formats which this crate can't decode are
+  /// recognized by their format code as well.
+  pub fn codec_name(&self) -> &'static str {
+    match *self {
+      Format::PCM           => "Linear PCM",
+      Format::IEEEFloat     => "IEEE 754 Floating Point",
+      Format::Extensible    => "Extensible",
+      Format::Unknown(0x02) => "Microsoft ADPCM",
+      Format::Unknown(0x06) => "ITU G.711 A-law",
+      Format::Unknown(0x07) => "ITU G.711 u-law",
+      Format::Unknown(0x11) => "IMA ADPCM",
+      Format::Unknown(0x50) => "MPEG",
+      Format::Unknown(0x55) => "MPEG Layer III",
+      Format::Unknown(_)    => "Unknown"
+    }
+  }
+
+  /// The raw format code as stored in the file header.
+  pub fn code(&self) -> u16 {
+    match *self {
+      Format::PCM          => FORMAT_PCM,
+      Format::IEEEFloat    => FORMAT_IEEE,
+      Format::Extensible   => FORMAT_EXT,
+      Format::Unknown(val) => val
+    }
+  }
+}
+
+impl Display for Format {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Format::Unknown(code) => write!(f, "Unknown (0x{:04x})", code),
+      _                     => f.write_str(self.name())
     }
   }
 }
@@ -1,31 +1,117 @@
+#![cfg_attr(feature = "no_std", no_std)]
+
+#[cfg(not(feature = "no_std"))]
+extern crate core;
+#[cfg(feature = "no_std")]
+#[macro_use]
+extern crate alloc;
+#[cfg(not(feature = "no_std"))]
 extern crate memmap;
+#[cfg(all(unix, not(feature = "no_std")))]
+extern crate libc;
 extern crate byteorder;
+#[cfg(feature = "sha2")]
+extern crate sha2;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 
 pub mod error;
 pub mod speakers;
 pub mod formats;
+pub mod resample;
+pub mod options;
+pub mod chunks;
+pub mod metadata;
+pub mod compare;
+pub mod mix;
+pub mod buffer;
+#[cfg(not(feature = "no_std"))]
+pub mod writer;
+mod analysis;
 
 pub use self::error::WaveError;
-pub use self::speakers::SpeakerPosition;
+pub use self::speakers::{ChannelInfo, SpeakerPosition};
 pub use self::formats::Format;
-
-use std::io::{Seek,SeekFrom,Cursor};
-use std::fs::File;
+pub use self::resample::{ResampledIterator, ResampleQuality};
+pub use self::options::OpenOptions;
+pub use self::chunks::{Chunk, ChunkIterator};
+pub use self::metadata::{AcidChunk, Chapter, InstrumentChunk, Metadata, PeakChunk, PeakInfo};
+pub use self::compare::CompareResult;
+pub use self::mix::{mix_frames, MixIterator};
+pub use self::buffer::{AudioBuffer, AudioBufferFrames};
+pub use self::analysis::WaveStats;
+#[cfg(not(feature = "no_std"))]
+pub use self::writer::WaveWriter;
+
+use core::fmt;
+use core::ops::Deref;
+use core::cmp;
+use core::time::Duration;
+#[cfg(feature = "no_std")]
+use alloc::vec::Vec;
+#[cfg(feature = "no_std")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "no_std")]
+use alloc::sync::Arc;
+#[cfg(not(feature = "no_std"))]
+use std::sync::Arc;
+#[cfg(not(feature = "no_std"))]
+use std::io::{self, Read, Seek, SeekFrom, Write};
+#[cfg(not(feature = "no_std"))]
+use std::fs::{self, File};
+#[cfg(not(feature = "no_std"))]
+use std::path::{Path, PathBuf};
+#[cfg(all(any(unix, windows), not(feature = "no_std")))]
+use std::mem::ManuallyDrop;
+#[cfg(all(unix, not(feature = "no_std")))]
+use std::os::unix::io::{FromRawFd, RawFd};
+#[cfg(all(windows, not(feature = "no_std")))]
+use std::os::windows::io::{FromRawHandle, RawHandle};
+#[cfg(not(feature = "no_std"))]
 use memmap::Mmap;
+#[cfg(all(feature = "timeout", not(feature = "no_std")))]
+use std::sync::mpsc;
+#[cfg(all(feature = "timeout", not(feature = "no_std")))]
+use std::thread;
+#[cfg(feature = "sha2")]
+use sha2::{Digest, Sha256};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{ByteOrder, BigEndian, LittleEndian};
 
 const RIFF : u32 = 0x46464952;
+const RIFX : u32 = 0x58464952;
+const RF64 : u32 = 0x34364652;
 const WAVE : u32 = 0x45564157;
 const FMT_ : u32 = 0x20746d66;
 const DATA : u32 = 0x61746164;
-const LIST : u32 = 0x5453494c;
 const FACT : u32 = 0x74636166;
+const ACID : u32 = 0x64696361;
+const INST : u32 = 0x74736e69;
+const PEAK : u32 = 0x4b414550;
+const DS64 : u32 = 0x34367364;
+
+// the GUIDs identifying a Sony Wave64 file, as stored on disk.
+const W64_RIFF : [u8; 16] = [0x72, 0x69, 0x66, 0x66, 0x2e, 0x91, 0xcf, 0x11,
+                             0xa5, 0xd6, 0x28, 0xdb, 0x04, 0xc1, 0x00, 0x00];
+const W64_WAVE : [u8; 16] = [0x77, 0x61, 0x76, 0x65, 0xf3, 0xac, 0xd3, 0x11,
+                             0x8c, 0xd1, 0x00, 0xc0, 0x4f, 0x8e, 0xdb, 0x8a];
+
+// sample rates considered plausible by `WaveFile::detect_sample_rate_heuristic()`.
+const STANDARD_SAMPLE_RATES : [u32; 9] = [8000, 11025, 22050, 44100, 48000,
+                                          88200, 96000, 176400, 192000];
+
+// the sub-format GUID of an extensible `fmt ` chunk is the format code
+// followed by these bytes.
+const SUBFORMAT_GUID_TAIL : [u8; 14] = [0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80,
+                                        0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71];
 
 /// Contains information included in the wavefile's header section,
 /// describing the format, sample size, and number of audio channels
 /// present.
 #[derive(Debug,Copy,Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WaveInfo {
   /// Which encoding format this file uses.
   /// If the format is `Format::Extensible`, then the actual audio format is
@@ -57,13 +143,199 @@ pub struct WaveInfo {
   pub subformat:       Option<Format>
 }
 
+impl Default for WaveInfo {
+  /// Stereo 16-bit PCM at 44100 Hz, with no frames.
+  fn default() -> WaveInfo {
+    let channels        = 2;
+    let sample_rate     = 44100;
+    let bits_per_sample = 16;
+    let block_align     = channels * bits_per_sample / 8;
+
+    WaveInfo {
+      audio_format:    Format::PCM,
+      channels,
+      sample_rate,
+      byte_rate:       sample_rate * block_align as u32,
+      block_align,
+      bits_per_sample,
+      total_frames:    0,
+      valid_bps:       None,
+      channel_mask:    None,
+      subformat:       None
+    }
+  }
+}
+
+impl WaveInfo {
+  /// The format the audio data is actually encoded in: `subformat` for
+  /// `Format::Extensible` files, `audio_format` otherwise.
+  pub fn data_format(&self) -> Format {
+    match (self.audio_format, self.subformat) {
+      (Format::Extensible, Some(subformat)) => subformat,
+      (format, _)                           => format
+    }
+  }
+
+  /// Returns `true` if audio described by `other` can be combined with audio
+  /// described by `self` (e.g. concatenated or mixed) without conversion:
+  /// both must have the same sample rate, channel count, bits per sample
+  /// and data format.
+  pub fn compatible_with(&self, other: &WaveInfo) -> bool {
+    self.compatibility_error(other).is_none()
+  }
+
+  /// Like `compatible_with()`, but returns a `WaveError::Incompatible`
+  /// describing the first mismatching field, or `None` if the two are
+  /// compatible.
+  pub fn compatibility_error(&self, other: &WaveInfo) -> Option<WaveError> {
+    let msg = if self.sample_rate != other.sample_rate {
+      format!("sample rate {} does not match {}", other.sample_rate, self.sample_rate)
+    } else if self.channels != other.channels {
+      format!("{} channel(s) do not match {}", other.channels, self.channels)
+    } else if self.bits_per_sample != other.bits_per_sample {
+      format!("{} bits per sample do not match {}", other.bits_per_sample, self.bits_per_sample)
+    } else if self.data_format() != other.data_format() {
+      format!("format {:?} does not match {:?}", other.data_format(), self.data_format())
+    } else {
+      return None;
+    };
+
+    Some(WaveError::Incompatible(msg))
+  }
+
+  /// Serializes this header as a complete little-endian `fmt ` chunk,
+  /// including the chunk id and size.  For `Format::Extensible` the 22 byte
+  /// extension with `valid_bps`, `channel_mask` and the sub-format GUID is
+  /// included as well.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveInfo;
+  ///
+  /// let bytes = WaveInfo::default().to_fmt_chunk_bytes();
+  ///
+  /// assert_eq!(&bytes[..4], b"fmt ");
+  /// assert_eq!(bytes.len(), 24);
+  /// ```
+  pub fn to_fmt_chunk_bytes(&self) -> Vec<u8> {
+    let extensible = self.audio_format == Format::Extensible;
+    let size : u32 = if extensible { 40 } else { 16 };
+
+    let mut bytes = Vec::with_capacity(8 + size as usize);
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&size.to_le_bytes());
+    bytes.extend_from_slice(&self.audio_format.code().to_le_bytes());
+    bytes.extend_from_slice(&self.channels.to_le_bytes());
+    bytes.extend_from_slice(&self.sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&self.byte_rate.to_le_bytes());
+    bytes.extend_from_slice(&self.block_align.to_le_bytes());
+    bytes.extend_from_slice(&self.bits_per_sample.to_le_bytes());
+
+    if extensible {
+      let valid_bps = self.valid_bps.unwrap_or(self.bits_per_sample);
+      let subformat = self.subformat.unwrap_or(Format::PCM);
+
+      bytes.extend_from_slice(&22u16.to_le_bytes());
+      bytes.extend_from_slice(&valid_bps.to_le_bytes());
+      bytes.extend_from_slice(&self.channel_mask.unwrap_or(0).to_le_bytes());
+      bytes.extend_from_slice(&subformat.code().to_le_bytes());
+      bytes.extend_from_slice(&SUBFORMAT_GUID_TAIL);
+    }
+
+    bytes
+  }
+
+  /// Serializes this header as a JSON object, e.g. for exchanging it with a
+  /// web service.  Requires the `serde` feature.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveInfo;
+  ///
+  /// let json = WaveInfo::default().to_json_string().unwrap();
+  /// let info = WaveInfo::from_json_str(&json).unwrap();
+  ///
+  /// assert_eq!(info.sample_rate, 44100);
+  /// ```
+  #[cfg(feature = "serde")]
+  pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
+    serde_json::to_string(self)
+  }
+
+  /// Parses a header serialized by `to_json_string()`.  Requires the
+  /// `serde` feature.
+  #[cfg(feature = "serde")]
+  pub fn from_json_str(s: &str) -> Result<WaveInfo, serde_json::Error> {
+    serde_json::from_str(s)
+  }
+}
+
+// The bytes backing a `WaveFile`: either a memory mapped file on disk or a
+// buffer owned by the `WaveFile` itself.
+enum Buffer {
+  #[cfg(not(feature = "no_std"))]
+  Mapped(Mmap),
+  Owned(Vec<u8>)
+}
+
+impl Deref for Buffer {
+  type Target = [u8];
+
+  fn deref(&self) -> &[u8] {
+    match *self {
+      #[cfg(not(feature = "no_std"))]
+      Buffer::Mapped(ref mmap) => mmap.as_ref(),
+      Buffer::Owned(ref bytes) => bytes.as_ref()
+    }
+  }
+}
+
 pub struct WaveFile {
-  mmap:        Mmap,
+  buffer:      Buffer,
   data_offset: u64,
-  data_size:   u32,
+  data_size:   u64,
+  big_endian:  bool,
+  wave64:      bool,
+  rf64:        bool,
+  fact_length: Option<u32>,
+  acid:        Option<AcidChunk>,
+  instrument:  Option<InstrumentChunk>,
+  peak:        Option<PeakChunk>,
+  lenient:     bool,
+  #[cfg(not(feature = "no_std"))]
+  path:        Option<PathBuf>,
   info:        WaveInfo
 }
 
+/// A `WaveFile` whose contents are held in memory rather than mapped from
+/// disk.  See `WaveFile::clone_into_memory()`.
+pub type OwnedWaveFile = WaveFile;
+
+/// A path together with the result of opening it.
+/// See `WaveFile::open_directory()`.
+#[cfg(not(feature = "no_std"))]
+pub type DirectoryEntry = (PathBuf, Result<WaveFile, WaveError>);
+
+/// Returns `true` if `path` has a `.wav` or `.wave` extension, in any case;
+/// e.g. `take.WAV`.  This is the check `WaveFile::open_directory()` uses to
+/// select files.  The file itself is not accessed.
+///
+/// # Example
+///
+/// ```
+/// use std::path::Path;
+/// use wavefile::is_wav_path;
+///
+/// assert!(is_wav_path(Path::new("/music/Take 1.Wav")));
+/// assert!(!is_wav_path(Path::new("/music/take-1.mp3")));
+/// ```
+#[cfg(not(feature = "no_std"))]
+pub fn is_wav_path(path: &Path) -> bool {
+  path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("wav") || ext.eq_ignore_ascii_case("wave"))
+}
+
 /// An iterator which yields successive `Frames` of audio from the associated
 /// wavefile.
 pub struct WaveFileIterator<'a> {
@@ -74,6 +346,68 @@ pub struct WaveFileIterator<'a> {
   bytes_per_sample: u16,
 }
 
+/// An iterator which yields successive `Frames` of audio together with their
+/// index in the wavefile.  See `WaveFileIterator::enumerate_frames()`.
+pub struct FrameEnumerator<'a> {
+  frames: WaveFileIterator<'a>
+}
+
+/// An iterator which yields every `n`th `Frame` of audio from the associated
+/// wavefile.  See `WaveFileIterator::step_by_frames()`.
+pub struct StepIterator<'a> {
+  frames: WaveFileIterator<'a>,
+  step:   usize
+}
+
+/// An iterator which yields `Frames` with samples mapped from `[-1, 1]` to
+/// a different range.  See `WaveFile::iter_in_range()`.
+pub struct RangedIterator<'a> {
+  frames: WaveFileIterator<'a>,
+  scale:  f32,
+  offset: f32
+}
+
+/// An iterator which yields `Frames` containing only some of the channels
+/// of the associated wavefile.  See `WaveFile::iter_channels()`.
+pub struct ChannelMaskedIterator<'a> {
+  frames: WaveFileIterator<'a>,
+  mask:   u64
+}
+
+/// An iterator which yields successive `Frames` of audio together with their
+/// time offset from the start of the wavefile.
+/// See `WaveFile::iter_with_timestamps()`.
+pub struct TimestampedIterator<'a> {
+  frames: WaveFileIterator<'a>
+}
+
+/// An iterator which yields windows of consecutive `Frames`, which overlap
+/// if the hop size is smaller than the window size.
+/// See `WaveFile::iter_overlapping_windows()`.
+pub struct OverlapWindowIterator<'a> {
+  frames:  WaveFileIterator<'a>,
+  window:  Vec<Frame>,
+  size:    usize,
+  hop:     usize,
+  started: bool
+}
+
+/// An iterator which yields successive `Frames` of audio from a shared
+/// wavefile.  Unlike `WaveFileIterator`, it owns a reference count rather
+/// than borrowing the file, so it can be moved to another thread.
+/// See `WaveFile::iter_owned()`.
+pub struct OwnedWaveFileIterator {
+  file:  Arc<WaveFile>,
+  frame: usize
+}
+
+/// A saved position of a `WaveFileIterator`, which can be used to resume
+/// iteration later on.  See `WaveFileIterator::save_position()`.
+#[derive(Debug,Copy,Clone,Default,PartialEq,Eq)]
+pub struct PlaybackPosition {
+  frame: usize
+}
+
 /// Represents a single frame of audio, containing one sample per audio channel.
 /// For example, a mono audio file will contain only one sample; a stereo file
 /// will contain two.
@@ -82,6 +416,13 @@ pub type Frame = Vec<f32>;
 impl WaveFile {
   /// Constructs a new `WaveFile`.
   ///
+  /// Following the Unix convention, the path `-` reads the file from
+  /// standard input instead; see `open_stdin()`.
+  ///
+  /// The file is memory mapped, as with `open_mmap()`.  If the `safe`
+  /// feature is enabled, it is read into memory instead, as with
+  /// `open_safe()`.
+  ///
   /// # Example
   ///
   /// ```
@@ -92,10 +433,321 @@ impl WaveFile {
   ///   Err(e) => panic!("Couldn't open example file: {}", e)
   /// };
   /// ```
-  pub fn open<S: Into<String>>(path: S) -> Result<WaveFile, WaveError> {
-    let filename = path.into();
-    let file = File::open(filename)?;
-    let mmap = unsafe { Mmap::map(&file)? };
+  #[cfg(not(feature = "no_std"))]
+  pub fn open<P: AsRef<Path>>(path: P) -> Result<WaveFile, WaveError> {
+    OpenOptions::new().open(path)
+  }
+
+  /// Like `open()`, but always memory maps the file, even if the `safe`
+  /// feature is enabled.  Only the parts of the file which are accessed are
+  /// read from disk.
+  #[cfg(not(feature = "no_std"))]
+  pub fn open_mmap<P: AsRef<Path>>(path: P) -> Result<WaveFile, WaveError> {
+    OpenOptions::new().open_mmap(path)
+  }
+
+  /// Like `open()`, but reads the entire file into memory with
+  /// `std::fs::read()` instead of memory mapping it.  This works in
+  /// environments where `mmap` is unavailable or prohibited, such as WASM
+  /// or sandboxed processes, and is unaffected by the file being modified
+  /// while open, at the cost of memory proportional to the file size.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open_safe("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// assert_eq!(wav.channels(), 2);
+  /// ```
+  #[cfg(not(feature = "no_std"))]
+  pub fn open_safe<P: AsRef<Path>>(path: P) -> Result<WaveFile, WaveError> {
+    OpenOptions::new().open_safe(path)
+  }
+
+  /// Constructs a new `WaveFile`.  Identical to `open()`.
+  #[cfg(not(feature = "no_std"))]
+  pub fn open_path<P: AsRef<Path>>(path: P) -> Result<WaveFile, WaveError> {
+    WaveFile::open(path)
+  }
+
+  /// Like `open()`, but discards any error.  Useful for `filter_map`
+  /// pipelines that should silently skip files which can't be opened.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let files = vec!["./fixtures/test-s24le.wav", "./fixtures/missing.wav"];
+  /// let wavs  = files.iter().filter_map(WaveFile::try_open).collect::<Vec<_>>();
+  ///
+  /// assert_eq!(wavs.len(), 1);
+  /// ```
+  #[cfg(not(feature = "no_std"))]
+  pub fn try_open<P: AsRef<Path>>(path: P) -> Option<WaveFile> {
+    WaveFile::open(path).ok()
+  }
+
+  /// Opens every file with a `.wav` or `.wave` extension (in any case) in
+  /// the directory `dir`, as determined by `is_wav_path()`, returning each
+  /// path along with the result of opening it.  The results are sorted by
+  /// path.
+  ///
+  /// Failing to open an individual file does not abort the batch; only an
+  /// error reading the directory itself is returned as `Err`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// for (path, result) in WaveFile::open_directory("./fixtures").unwrap() {
+  ///   match result {
+  ///     Ok(wav) => println!("{}: {} frames", path.display(), wav.len()),
+  ///     Err(e)  => println!("{}", e)
+  ///   }
+  /// }
+  /// ```
+  #[cfg(not(feature = "no_std"))]
+  pub fn open_directory<P: AsRef<Path>>(dir: P) -> Result<Vec<DirectoryEntry>, io::Error> {
+    let mut paths = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+      let path = entry?.path();
+
+      if is_wav_path(&path) && path.is_file() {
+        paths.push(path);
+      }
+    }
+
+    paths.sort();
+
+    Ok(paths.into_iter().map(|path| {
+      let result = WaveFile::open(&path);
+      (path, result)
+    }).collect())
+  }
+
+  /// Determines the format of the audio data in the file at `path` by
+  /// reading only its headers up to the `fmt ` chunk, without mapping the
+  /// rest of the file.  For `Format::Extensible` files, the subformat is
+  /// returned.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::{WaveFile,Format};
+  ///
+  /// let format = WaveFile::detect_format("./fixtures/test-f32le.wav").unwrap();
+  ///
+  /// assert_eq!(format, Format::IEEEFloat);
+  /// ```
+  #[cfg(not(feature = "no_std"))]
+  pub fn detect_format<P: AsRef<Path>>(path: P) -> Result<Format, WaveError> {
+    let path = path.as_ref();
+
+    WaveFile::read_format_from(File::open(path)?).map_err(|e| e.with_path(path))
+  }
+
+  /// Constructs a new `WaveFile` from an in-memory buffer containing the
+  /// complete contents of a wavefile.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use std::fs;
+  /// use wavefile::WaveFile;
+  ///
+  /// let bytes = fs::read("./fixtures/test-s24le.wav").unwrap();
+  /// let wav   = WaveFile::from_vec(bytes).unwrap();
+  ///
+  /// assert_eq!(wav.channels(), 2);
+  /// ```
+  pub fn from_vec(bytes: Vec<u8>) -> Result<WaveFile, WaveError> {
+    OpenOptions::new().open_vec(bytes)
+  }
+
+  /// Constructs a new `WaveFile` by reading wave data from standard input,
+  /// e.g. when used at the end of a pipeline like
+  /// `ffmpeg -i input.mp4 -f wav - | my_tool`.
+  ///
+  /// Standard input cannot be memory mapped, so the entire stream is read
+  /// into memory before parsing.  Memory usage is therefore proportional to
+  /// the size of the piped file.
+  #[cfg(not(feature = "no_std"))]
+  pub fn open_stdin() -> Result<WaveFile, WaveError> {
+    OpenOptions::new().open_stdin()
+  }
+
+  /// Like `open()`, but makes sure the entire file has been paged into
+  /// memory before returning.  This avoids page fault latency during
+  /// playback from slow disks or network filesystems, at the cost of
+  /// reading the whole file up front.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open_with_preload("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// assert_eq!(wav.channels(), 2);
+  /// ```
+  #[cfg(not(feature = "no_std"))]
+  pub fn open_with_preload<P: AsRef<Path>>(path: P) -> Result<WaveFile, WaveError> {
+    let file = WaveFile::open(path)?;
+    file.preload();
+
+    Ok(file)
+  }
+
+  /// Like `open_mmap()`, but also locks the entire mapping into memory with
+  /// `mlock(2)`, so that reading samples never page faults.  This makes it
+  /// safe to access the file from a real-time audio thread.  The file is
+  /// always memory mapped, even if the `safe` feature is enabled.
+  ///
+  /// Returns an `IoError` if the pages can't be locked, most commonly
+  /// because the file exceeds the locked memory limit of the process
+  /// (`RLIMIT_MEMLOCK`).  The lock is released by `unlock()` or when the
+  /// `WaveFile` is dropped.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// if let Ok(wav) = WaveFile::open_mlock("./fixtures/test-s16be-rifx.wav") {
+  ///   assert_eq!(wav.channels(), 2);
+  ///   wav.unlock();
+  /// }
+  /// ```
+  #[cfg(all(unix, not(feature = "no_std")))]
+  pub fn open_mlock<P: AsRef<Path>>(path: P) -> Result<WaveFile, WaveError> {
+    let file = WaveFile::open_mmap(path)?;
+
+    let result = unsafe {
+      libc::mlock(file.buffer.as_ptr() as *const libc::c_void, file.buffer.len())
+    };
+
+    if result != 0 {
+      return Err(file.in_file(WaveError::IoError(io::Error::last_os_error())));
+    }
+
+    Ok(file)
+  }
+
+  /// Releases the memory lock taken by `open_mlock()`, allowing the pages of
+  /// this file to be swapped out again.  Does nothing if the file isn't
+  /// locked.
+  #[cfg(all(unix, not(feature = "no_std")))]
+  pub fn unlock(&self) {
+    unsafe {
+      libc::munlock(self.buffer.as_ptr() as *const libc::c_void, self.buffer.len());
+    }
+  }
+
+  /// Like `open()`, but gives up with an `IoError` of kind
+  /// `io::ErrorKind::TimedOut` if the file hasn't been opened after
+  /// `timeout`; e.g. because a network filesystem stopped responding.
+  /// Requires the `timeout` feature.
+  ///
+  /// The file is opened on a background thread.  If the timeout expires,
+  /// that thread is left to finish (or block) on its own, and the file is
+  /// closed as soon as it has been opened.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open_with_timeout("./fixtures/test-s24le.wav", Duration::from_secs(5)).unwrap();
+  ///
+  /// assert_eq!(wav.channels(), 2);
+  /// ```
+  #[cfg(all(feature = "timeout", not(feature = "no_std")))]
+  pub fn open_with_timeout<P: AsRef<Path>>(path: P, timeout: Duration) -> Result<WaveFile, WaveError> {
+    let (sender, receiver) = mpsc::channel();
+    let owned              = path.as_ref().to_path_buf();
+
+    thread::spawn(move || {
+      // the receiver is gone if the timeout has already expired.
+      let _ = sender.send(WaveFile::open(owned));
+    });
+
+    match receiver.recv_timeout(timeout) {
+      Ok(result) => result,
+      Err(mpsc::RecvTimeoutError::Timeout) => {
+        let msg = format!("Opening the file took longer than {:?}", timeout);
+        Err(WaveError::IoError(io::Error::new(io::ErrorKind::TimedOut, msg)).with_path(path))
+      },
+      Err(mpsc::RecvTimeoutError::Disconnected) => {
+        let e = io::Error::other("The thread opening the file stopped unexpectedly");
+        Err(WaveError::IoError(e).with_path(path))
+      }
+    }
+  }
+
+  /// Constructs a new `WaveFile` by memory mapping an already open file
+  /// descriptor, e.g. one handed over by a plugin host.
+  ///
+  /// The descriptor is not closed by this function or by the returned
+  /// `WaveFile`; it only needs to remain open for the duration of the call.
+  ///
+  /// # Safety
+  ///
+  /// `fd` must be a valid, open file descriptor referring to a regular file.
+  #[cfg(all(unix, not(feature = "no_std")))]
+  pub unsafe fn from_raw_fd(fd: RawFd) -> Result<WaveFile, WaveError> {
+    let file = ManuallyDrop::new(File::from_raw_fd(fd));
+    let mmap = Mmap::map(&file)?;
+
+    WaveFile::from_buffer(Buffer::Mapped(mmap), &OpenOptions::new())
+  }
+
+  /// Constructs a new `WaveFile` by memory mapping an already open file
+  /// handle, e.g. one handed over by a plugin host.
+  ///
+  /// The handle is not closed by this function or by the returned
+  /// `WaveFile`; it only needs to remain open for the duration of the call.
+  ///
+  /// # Safety
+  ///
+  /// `handle` must be a valid, open handle referring to a regular file.
+  #[cfg(all(windows, not(feature = "no_std")))]
+  pub unsafe fn from_raw_handle(handle: RawHandle) -> Result<WaveFile, WaveError> {
+    let file = ManuallyDrop::new(File::from_raw_handle(handle));
+    let mmap = Mmap::map(&file)?;
+
+    WaveFile::from_buffer(Buffer::Mapped(mmap), &OpenOptions::new())
+  }
+
+  /// Copies the contents of this file into memory, returning a `WaveFile`
+  /// that no longer depends on the file on disk.  The returned value can be
+  /// kept around (e.g. in a cache) after the original file is closed,
+  /// modified or deleted.
+  pub fn clone_into_memory(&self) -> OwnedWaveFile {
+    WaveFile {
+      buffer:      Buffer::Owned(self.buffer.to_vec()),
+      data_offset: self.data_offset,
+      data_size:   self.data_size,
+      big_endian:  self.big_endian,
+      wave64:      self.wave64,
+      rf64:        self.rf64,
+      fact_length: self.fact_length,
+      acid:        self.acid,
+      instrument:  self.instrument,
+      peak:        self.peak.clone(),
+      lenient:     self.lenient,
+      #[cfg(not(feature = "no_std"))]
+      path:        self.path.clone(),
+      info:        self.info
+    }
+  }
+
+  fn from_buffer(buffer: Buffer, options: &OpenOptions) -> Result<WaveFile, WaveError> {
     let info = WaveInfo {
       audio_format:    Format::PCM,
       channels:        0,
@@ -108,9 +760,24 @@ impl WaveFile {
       channel_mask:    None,
       subformat:       None
     };
-    let mut file = WaveFile { mmap: mmap, data_offset: 0, data_size: 0, info: info };
+    let mut file = WaveFile {
+      buffer,
+      data_offset: 0,
+      data_size:   0,
+      big_endian:  false,
+      wave64:      false,
+      rf64:        false,
+      fact_length: None,
+      acid:        None,
+      instrument:  None,
+      peak:        None,
+      lenient:     options.lenient,
+      #[cfg(not(feature = "no_std"))]
+      path:        None,
+      info
+    };
 
-    file.read_chunks()?;
+    file.read_chunks(options)?;
 
     Ok(file)
   }
@@ -125,400 +792,2664 @@ impl WaveFile {
     self.info.sample_rate
   }
 
+  /// Guesses the actual sample rate of a file whose header may be broken,
+  /// for use in repair tools.
+  ///
+  /// If `byte_rate == sample_rate * block_align` holds, the header is
+  /// trusted and `sample_rate()` is returned.  Otherwise `byte_rate /
+  /// block_align` is returned if it is a standard sample rate such as 44100
+  /// or 48000 Hz.  Returns `None` if neither gives a plausible rate.
+  ///
+  /// Files with an inconsistent header can only be opened with
+  /// `OpenOptions::lenient()`.
+  pub fn detect_sample_rate_heuristic(&self) -> Option<u32> {
+    let block_align = self.info.block_align as u64;
+
+    if self.info.sample_rate != 0 && self.info.byte_rate as u64 == self.info.sample_rate as u64 * block_align {
+      return Some(self.info.sample_rate);
+    }
+
+    if block_align == 0 || !(self.info.byte_rate as u64).is_multiple_of(block_align) {
+      return None;
+    }
+
+    let rate = (self.info.byte_rate as u64 / block_align) as u32;
+
+    if STANDARD_SAMPLE_RATES.contains(&rate) {
+      Some(rate)
+    } else {
+      None
+    }
+  }
+
+  /// Returns the header of this file with `total_frames` recomputed from
+  /// the number of bytes actually following the start of the `data` chunk,
+  /// rather than the size declared in its header.  This fixes files which
+  /// were truncated, or written by a streaming writer which never filled in
+  /// the chunk size.  A trailing partial frame is not counted.
+  ///
+  /// Everything up to the end of the file is considered audio, so any
+  /// chunks after the `data` chunk are counted as well; this is meant for
+  /// repair tools, for files where `data` is known to be the last chunk.
+  /// The result can be passed to `WaveWriter` to write a fixed copy.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav  = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  /// let info = wav.repair_header();
+  ///
+  /// assert_eq!(info.total_frames as usize, wav.len());
+  /// ```
+  pub fn repair_header(&self) -> WaveInfo {
+    let available = (self.buffer.len() as u64).saturating_sub(self.data_offset);
+    let frames    = available / self.bytes_per_frame();
+
+    WaveInfo { total_frames: cmp::min(frames, u32::MAX as u64) as u32, ..self.info }
+  }
+
   /// The total number of frames present in the file.
   /// Each frame will contain `channels()` number of samples.
   pub fn len(&self) -> usize {
     self.info.total_frames as usize
   }
 
-  /// The duration in milliseconds of the file.
-  pub fn duration(&self) -> u32 {
-    self.len() as u32 * 1000 / self.sample_rate()
+  /// The total number of individual samples in the file, across all
+  /// channels, i.e. `len() * channels()`.
+  pub fn total_samples(&self) -> u64 {
+    self.info.total_frames as u64 * self.info.channels as u64
   }
 
-  pub fn bits_per_sample(&self) -> u16 {
-    self.info.bits_per_sample
+  /// The number of samples in each channel.  The same as `len()`, as a
+  /// `u64` for consistency with `total_samples()`.
+  pub fn sample_count_per_channel(&self) -> u64 {
+    self.info.total_frames as u64
   }
 
-  pub fn data_format(&self) -> Format {
-    if self.info.audio_format == Format::Extensible {
-      self.info.subformat.unwrap()
-    } else {
-      self.info.audio_format
-    }
+  /// Returns `true` if the file contains no audio frames.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
   }
 
-  pub fn speakers(&self) -> Option<Vec<SpeakerPosition>> {
-    match self.info.channel_mask {
-      None       => None,
-      Some(mask) => Some(SpeakerPosition::decode(mask as isize))
-    }
+  /// The duration in milliseconds of the file.
+  pub fn duration(&self) -> u32 {
+    self.len() as u32 * 1000 / self.sample_rate()
   }
 
-  /// Returns a copy of the `WaveInfo` for this file,
-  /// parsed from the file header.
-  pub fn info(&self) -> WaveInfo {
-    self.info
+  /// The duration in microseconds of the file.  Unlike `duration()`, this
+  /// does not lose up to a millisecond to rounding.
+  pub fn duration_micros(&self) -> u64 {
+    if self.sample_rate() == 0 {
+      return 0;
+    }
+
+    self.len() as u64 * 1_000_000 / self.sample_rate() as u64
   }
 
-  /// Returns an iterator which yields each individual `Frame` successively
-  /// until it reaches the end of the file.
+  /// The time elapsed from the start of the file up to the frame at index
+  /// `frame`, e.g. for displaying a timestamp.
   ///
   /// # Example
   ///
-  /// ```no_run
+  /// ```
+  /// use std::time::Duration;
   /// use wavefile::WaveFile;
   ///
   /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
   ///
-  /// for frame in wav.iter() {
-  ///   println!("{:?}", frame);
-  /// }
+  /// assert_eq!(wav.elapsed_at(24000), Duration::from_millis(500));
+  /// assert_eq!(wav.frame_at_time(Duration::from_millis(500)), 24000);
   /// ```
-  pub fn iter(&self) -> WaveFileIterator {
-    let bytes_per_sample = self.info.bits_per_sample / 8;
-    WaveFileIterator {
-      file:             &self,
-      pos:              0,
-      base:             self.data_offset,
-      end:              self.data_offset + self.data_size as u64,
-      bytes_per_sample: bytes_per_sample
+  #[inline]
+  pub fn elapsed_at(&self, frame: usize) -> Duration {
+    if self.sample_rate() == 0 {
+      return Duration::from_secs(0);
     }
+
+    Duration::from_secs_f64(frame as f64 / self.sample_rate() as f64)
   }
 
-  fn read_format_chunk(info: &mut WaveInfo, cursor: &mut Cursor<&[u8]>) -> Result<(), WaveError> {
-    let fmt = cursor.read_u16::<LittleEndian>()?;
+  /// The index of the frame closest to time `t` from the start of the file,
+  /// clamped to the frames actually present.  The inverse of `elapsed_at()`.
+  #[inline]
+  pub fn frame_at_time(&self, t: Duration) -> usize {
+    if self.is_empty() {
+      return 0;
+    }
+
+    // `f64::round()` is not available without `std`; adding 0.5 before
+    // truncating rounds the same way for positive values.
+    let frame = (t.as_secs_f64() * self.sample_rate() as f64 + 0.5) as usize;
+
+    cmp::min(frame, self.len() - 1)
+  }
+
+  /// Returns the sample for `channel` in the frame at index `frame`, reading
+  /// only the bytes of that one sample.  Returns `None` if either index is
+  /// out of bounds.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+  ///
+  /// assert_eq!(wav.sample_at(1, 1), Some(-0.03125));
+  /// assert_eq!(wav.sample_at(1, 2), None);
+  /// ```
+  pub fn sample_at(&self, frame: usize, channel: usize) -> Option<f32> {
+    if frame >= self.len() || channel >= self.channels() as usize {
+      return None;
+    }
+
+    let bps   = (self.info.bits_per_sample / 8) as u64;
+    let start = frame as u64 * self.bytes_per_frame() + channel as u64 * bps;
+    let bytes = self.data_bytes().get(start as usize..(start + bps) as usize)?;
+
+    let sample = if self.big_endian {
+      self.read_sample::<BigEndian>(bytes)
+    } else {
+      self.read_sample::<LittleEndian>(bytes)
+    };
+
+    sample.and_then(|sample| sample.first().cloned())
+  }
+
+  /// The average bitrate in bits per second, as declared by the `byte_rate`
+  /// in the header.  For PCM and float data this is simply
+  /// `sample_rate * channels * bits_per_sample`; for compressed formats
+  /// such as ADPCM or A-law it is the compressed bitrate.
+  pub fn bitrate_bps(&self) -> u64 {
+    self.info.byte_rate as u64 * 8
+  }
+
+  /// The average bitrate in kilobits per second.  See `bitrate_bps()`.
+  pub fn bitrate_kbps(&self) -> f64 {
+    self.bitrate_bps() as f64 / 1000.0
+  }
+
+  pub fn bits_per_sample(&self) -> u16 {
+    self.info.bits_per_sample
+  }
+
+  pub fn data_format(&self) -> Format {
+    self.info.data_format()
+  }
+
+  /// The name of `data_format()`, e.g. `"IEEE Float"`.  See `Format::name()`.
+  pub fn data_format_str(&self) -> &'static str {
+    self.data_format().name()
+  }
+
+  /// The format code of `data_format()` as stored in the file, e.g. `3` for
+  /// IEEE Float.  For `Format::Extensible` files, this is the code of the
+  /// subformat.
+  pub fn data_format_code(&self) -> u16 {
+    self.data_format().code()
+  }
+
+  /// Returns `true` if the file has exactly one channel.
+  pub fn is_mono(&self) -> bool {
+    self.channels() == 1
+  }
+
+  /// Returns `true` if the file has exactly two channels.
+  pub fn is_stereo(&self) -> bool {
+    self.channels() == 2
+  }
+
+  /// Returns `true` if the file has more than two channels.
+  pub fn is_surround(&self) -> bool {
+    self.channels() > 2
+  }
+
+  /// Returns `true` if the audio data is PCM encoded, either directly or
+  /// as the subformat of an extensible file.
+  pub fn is_pcm(&self) -> bool {
+    self.data_format() == Format::PCM
+  }
+
+  /// Returns `true` if the audio data is IEEE Float encoded, either directly
+  /// or as the subformat of an extensible file.
+  pub fn is_float(&self) -> bool {
+    self.data_format() == Format::IEEEFloat
+  }
+
+  /// Returns `true` if the audio data consists of integer samples.
+  /// Equivalent to `is_pcm()`.
+  pub fn is_integer(&self) -> bool {
+    self.is_pcm()
+  }
+
+  /// Returns `true` if the audio data is stored without lossy compression.
+  /// This holds for PCM and IEEE Float data; any other format (e.g. ADPCM
+  /// or A-law, which appear as `Format::Unknown`) is treated as lossy.
+  pub fn is_lossless(&self) -> bool {
+    match self.data_format() {
+      Format::PCM | Format::IEEEFloat         => true,
+      Format::Extensible | Format::Unknown(_) => false
+    }
+  }
+
+  /// The number of samples per channel stored in the `fact` chunk, if the
+  /// file has one.
+  ///
+  /// The `fact` chunk is required for compressed formats, where the frame
+  /// count can't be derived from the size of the data chunk.  Only PCM and
+  /// IEEE Float files can be opened, so `len()` is always computed from the
+  /// data chunk, and this value is informational only.
+  pub fn fact_sample_length(&self) -> Option<u32> {
+    self.fact_length
+  }
+
+  /// Tempo and loop information from the file's `acid` chunk, if present.
+  pub fn acid_info(&self) -> Option<&AcidChunk> {
+    self.acid.as_ref()
+  }
+
+  /// MIDI note and velocity ranges from the file's `inst` chunk, if present.
+  /// Samplers use these to decide which notes trigger the sample.
+  pub fn instrument_info(&self) -> Option<&InstrumentChunk> {
+    self.instrument.as_ref()
+  }
+
+  /// Pre-computed peak levels from the file's `PEAK` chunk, if present.
+  /// See `peak_sample()`.
+  pub fn peak_chunk(&self) -> Option<&PeakChunk> {
+    self.peak.as_ref()
+  }
+
+  /// Returns `true` if this is a big-endian `RIFX` file rather than a
+  /// regular little-endian `RIFF` file.
+  pub fn is_big_endian(&self) -> bool {
+    self.big_endian
+  }
+
+  /// Returns `true` if this is a Sony Wave64 (`.w64`) file, which uses
+  /// GUIDs and 64-bit sizes for its chunk headers.
+  pub fn is_wave64(&self) -> bool {
+    self.wave64
+  }
+
+  /// Returns `true` if this is an `RF64` file, which stores the sizes of
+  /// chunks larger than 4 GiB in a `ds64` chunk.
+  pub fn is_rf64(&self) -> bool {
+    self.rf64
+  }
+
+  pub fn speakers(&self) -> Option<Vec<SpeakerPosition>> {
+    self.info.channel_mask.map(|mask| SpeakerPosition::decode(mask as isize))
+  }
+
+  /// Describes each channel of the file, pairing its index with the speaker
+  /// position assigned to it by the channel mask.  Files without a channel
+  /// mask (i.e. anything but `Format::Extensible`) have no speaker positions.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::{WaveFile,SpeakerPosition};
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  ///
+  /// assert_eq!(wav.channels_info()[1].speaker, Some(SpeakerPosition::FrontRight));
+  /// ```
+  pub fn channels_info(&self) -> Vec<ChannelInfo> {
+    let speakers = self.speakers().unwrap_or_default();
+
+    (0..self.channels() as usize)
+      .map(|index| ChannelInfo { index, speaker: speakers.get(index).cloned() })
+      .collect()
+  }
+
+  /// Returns a one-line summary of the file's format, suitable for logging,
+  /// e.g. `48000 Hz / 24-bit PCM / Stereo (FrontLeft, FrontRight) / 10.456 s`.
+  pub fn format_description(&self) -> String {
+    let mut channels = match self.channels() {
+      1 => "Mono".to_string(),
+      2 => "Stereo".to_string(),
+      n => format!("{} channels", n)
+    };
+
+    if let Some(speakers) = self.speakers() {
+      let names = speakers.iter().map(|s| format!("{:?}", s)).collect::<Vec<_>>();
+      channels.push_str(&format!(" ({})", names.join(", ")));
+    }
+
+    let seconds = if self.sample_rate() == 0 {
+      0.0
+    } else {
+      self.len() as f64 / self.sample_rate() as f64
+    };
+
+    format!("{} Hz / {}-bit {} / {} / {:.3} s",
+            self.sample_rate(), self.bits_per_sample(), self.data_format(), channels, seconds)
+  }
+
+  /// Returns a multi-line, human-readable summary of the file's properties
+  /// in the style of `soxi`, for display by command-line tools.  Unlike
+  /// `format_description()`, each property is on a line of its own.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// println!("{}", wav.print_summary());
+  /// ```
+  pub fn print_summary(&self) -> String {
+    let mut lines = vec![
+      format!("Format      : {}", self.data_format()),
+      format!("Sample Rate : {} Hz", self.sample_rate()),
+      format!("Bit Depth   : {}", self.bits_per_sample()),
+      format!("Channels    : {}", self.channels())
+    ];
+
+    if let Some(speakers) = self.speakers() {
+      let names = speakers.iter().map(|s| format!("{:?}", s)).collect::<Vec<_>>();
+      lines.push(format!("Speakers    : {}", names.join(", ")));
+    }
+
+    let millis  = self.duration_micros() / 1000;
+    let seconds = millis / 1000;
+
+    lines.push(format!("Duration    : {:02}:{:02}:{:02}.{:03}",
+                       seconds / 3600, seconds / 60 % 60, seconds % 60, millis % 1000));
+    lines.push(format!("Frames      : {}", self.len()));
+    lines.push(format!("Byte Rate   : {} bytes/s", self.info.byte_rate));
+    lines.push(format!("File Size   : {} bytes", self.buffer.len()));
+
+    lines.join("\n")
+  }
+
+  /// The path this file was opened from, or `None` if it was constructed
+  /// from memory or standard input.
+  #[cfg(not(feature = "no_std"))]
+  pub fn path(&self) -> Option<&Path> {
+    self.path.as_deref()
+  }
+
+  /// Returns a copy of the `WaveInfo` for this file,
+  /// parsed from the file header.
+  pub fn info(&self) -> WaveInfo {
+    self.info
+  }
+
+  /// Computes a SHA-256 hash of the raw bytes in the `data` chunk.
+  ///
+  /// Only the audio payload is hashed, so two files with identical audio but
+  /// different metadata chunks will produce the same fingerprint.
+  /// Requires the `sha2` feature.
+  #[cfg(feature = "sha2")]
+  pub fn fingerprint(&self) -> [u8; 32] {
+    Sha256::digest(self.data_bytes()).into()
+  }
+
+  /// Returns the audio data of a 32-bit IEEE Float file as a slice of
+  /// `f32`, borrowed directly from the file without copying or converting
+  /// any samples.  Samples are interleaved, one per channel for each frame.
+  ///
+  /// Returns `None` unless the file contains little-endian 32-bit float
+  /// data which is suitably aligned in memory, i.e. the data chunk starts at
+  /// a multiple of four bytes (as it does in a canonical 44 byte header).
+  /// Only available on little-endian targets.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  ///
+  /// if let Some(samples) = wav.as_f32_slice() {
+  ///   assert_eq!(samples.len(), wav.len() * wav.channels() as usize);
+  /// }
+  /// ```
+  #[cfg(target_endian = "little")]
+  pub fn as_f32_slice(&self) -> Option<&[f32]> {
+    if self.big_endian || self.data_format() != Format::IEEEFloat || self.info.bits_per_sample != 32 {
+      return None;
+    }
+
+    let bytes = self.data_bytes();
+    let bytes = &bytes[..bytes.len() - bytes.len() % self.bytes_per_frame() as usize];
+
+    // every bit pattern is a valid `f32`, so reinterpreting the bytes is
+    // safe as long as the slice is aligned, which `align_to` checks for us.
+    match unsafe { bytes.align_to::<f32>() } {
+      (&[], samples, &[]) => Some(samples),
+      _                   => None
+    }
+  }
+
+  /// Like `as_f32_slice()`, but for 16-bit PCM files: returns the raw,
+  /// interleaved samples as a slice of `i16` borrowed directly from the
+  /// file, e.g. for handing them to an audio API expecting `&[i16]`.
+  ///
+  /// Returns `None` for any other format or bit depth, for big-endian files
+  /// and if the data chunk starts at an odd offset.  Only available on
+  /// little-endian targets.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-acid.wav").unwrap();
+  ///
+  /// if let Some(samples) = wav.raw_pcm_slice_i16() {
+  ///   assert_eq!(samples.len(), wav.len());
+  /// }
+  /// ```
+  #[cfg(target_endian = "little")]
+  pub fn raw_pcm_slice_i16(&self) -> Option<&[i16]> {
+    if self.big_endian || self.data_format() != Format::PCM || self.info.bits_per_sample != 16 {
+      return None;
+    }
+
+    let bytes = self.data_bytes();
+    let bytes = &bytes[..bytes.len() - bytes.len() % self.bytes_per_frame() as usize];
+
+    // as for `as_f32_slice()`, every bit pattern is a valid `i16`.
+    match unsafe { bytes.align_to::<i16>() } {
+      (&[], samples, &[]) => Some(samples),
+      _                   => None
+    }
+  }
+
+  /// Returns the raw bytes of the file header, i.e. everything from the
+  /// start of the file up to the first sample in the data chunk.  This
+  /// includes any chunks (and padding) preceding the audio data.
+  pub fn header_bytes(&self) -> &[u8] {
+    &self.buffer[..self.data_offset as usize]
+  }
+
+  /// Formats `header_bytes()` as a hex dump, with one line of sixteen bytes
+  /// each showing the offset, the bytes in hex and their ASCII representation:
+  ///
+  /// ```text
+  /// 00000000  52 49 46 46 24 f3 2d 00  57 41 56 45 66 6d 74 20  |RIFF$.-.WAVEfmt |
+  /// ```
+  pub fn header_hex_dump(&self) -> String {
+    let mut dump = String::new();
+
+    for (line, bytes) in self.header_bytes().chunks(16).enumerate() {
+      dump.push_str(&format!("{:08x} ", line * 16));
+
+      for i in 0..16 {
+        if i == 8 {
+          dump.push(' ');
+        }
+        match bytes.get(i) {
+          Some(byte) => dump.push_str(&format!(" {:02x}", byte)),
+          None       => dump.push_str("   ")
+        }
+      }
+
+      dump.push_str("  |");
+      for &byte in bytes {
+        dump.push(if (0x20..0x7f).contains(&byte) { byte as char } else { '.' });
+      }
+      dump.push_str("|\n");
+    }
+
+    dump
+  }
+
+  /// Writes a copy of the entire file, including all metadata chunks, to
+  /// `dest`.  Returns the number of bytes written.
+  ///
+  /// Unlike `std::fs::copy` this does not reopen the source file, but copies
+  /// the data that is already mapped into memory.
+  #[cfg(not(feature = "no_std"))]
+  pub fn copy_to_path<P: AsRef<Path>>(&self, dest: P) -> Result<u64, WaveError> {
+    let write = |path: &Path| -> Result<u64, WaveError> {
+      File::create(path)?.write_all(&self.buffer)?;
+      Ok(self.buffer.len() as u64)
+    };
+
+    write(dest.as_ref()).map_err(|e| e.with_path(dest))
+  }
+
+  /// Returns the `Frame` at `index`, or `None` if `index` is past the end of
+  /// the file.
+  ///
+  /// This is the random-access counterpart to `iter()`.  Frames are decoded
+  /// on demand and returned by value, which is why `WaveFile` does not
+  /// implement `std::ops::Index`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// assert!(wav.at(1000).is_some());
+  /// assert!(wav.at(wav.len()).is_none());
+  /// ```
+  pub fn at(&self, index: usize) -> Option<Frame> {
+    if index >= self.len() {
+      return None;
+    }
+
+    self.iter_from(index).next()
+  }
+
+  /// Returns an iterator which yields each individual `Frame` successively
+  /// until it reaches the end of the file.
+  ///
+  /// # Example
+  ///
+  /// ```no_run
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// for frame in wav.iter() {
+  ///   println!("{:?}", frame);
+  /// }
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn iter(&self) -> WaveFileIterator<'_> {
+    let bytes_per_sample = self.info.bits_per_sample / 8;
+    WaveFileIterator {
+      file:             self,
+      pos:              0,
+      base:             self.data_offset,
+      end:              self.data_offset.saturating_add(self.data_size),
+      bytes_per_sample
+    }
+  }
+
+  /// Returns an iterator like `iter()`, which starts at frame `start` rather
+  /// than at the beginning of the file.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// assert_eq!(wav.iter_from(1000).next(), wav.at(1000));
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn iter_from(&self, start: usize) -> WaveFileIterator<'_> {
+    let mut iter = self.iter();
+
+    iter.pos = if start < self.len() {
+      start as u64 * self.bytes_per_frame()
+    } else {
+      iter.end - iter.base
+    };
+
+    iter
+  }
+
+  /// Returns an iterator like `iter()`, which resumes at a position saved
+  /// by `WaveFileIterator::save_position()`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// let mut frames = wav.iter();
+  /// let first      = frames.by_ref().take(512).collect::<Vec<_>>();
+  /// let position   = frames.save_position();
+  ///
+  /// // ... later, e.g. in the next audio callback:
+  /// let next = wav.iter_from_position(position).next();
+  ///
+  /// assert_eq!(next, wav.at(512));
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn iter_from_position(&self, position: PlaybackPosition) -> WaveFileIterator<'_> {
+    self.iter_from(position.frame)
+  }
+
+  /// Returns an iterator like `iter()`, which maps each sample linearly from
+  /// `[-1, 1]` to `[out_min, out_max]`; e.g. `iter_in_range(0.0, 1.0)` for
+  /// unipolar samples, or `iter_in_range(-32767.0, 32767.0)` for the range
+  /// of 16-bit integers.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// for frame in wav.iter_in_range(0.0, 1.0).take(10) {
+  ///   assert!(frame.iter().all(|&sample| sample >= 0.0 && sample <= 1.0));
+  /// }
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn iter_in_range(&self, out_min: f32, out_max: f32) -> RangedIterator<'_> {
+    let scale = (out_max - out_min) / 2.0;
+
+    RangedIterator { frames: self.iter(), scale, offset: out_min + scale }
+  }
+
+  /// Returns an iterator like `iter()`, which yields only the channels whose
+  /// bit is set in `channel_mask`, in their original order.  For example,
+  /// `iter_channels(0b101)` yields frames with channels 0 and 2.  Bits
+  /// without a corresponding channel are ignored.
+  ///
+  /// Unlike `WaveInfo::channel_mask`, the bits refer to channel indices, not
+  /// speaker positions.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// for frame in wav.iter_channels(0b10).take(10) {
+  ///   println!("right: {}", frame[0]);
+  /// }
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn iter_channels(&self, channel_mask: u64) -> ChannelMaskedIterator<'_> {
+    ChannelMaskedIterator { frames: self.iter(), mask: channel_mask }
+  }
+
+  /// Returns an iterator over windows of `window_size` consecutive frames,
+  /// each starting `hop_size` frames after the previous one, e.g. as input
+  /// for a short-time Fourier transform.  Windows overlap if `hop_size` is
+  /// smaller than `window_size`.  Only complete windows are yielded.
+  ///
+  /// Only the frames of the current window are kept in memory, so memory
+  /// usage does not depend on the length of the file.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `window_size` or `hop_size` is zero.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// for window in wav.iter_overlapping_windows(1024, 256).take(10) {
+  ///   assert_eq!(window.len(), 1024);
+  /// }
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn iter_overlapping_windows(&self, window_size: usize, hop_size: usize) -> OverlapWindowIterator<'_> {
+    assert!(window_size != 0 && hop_size != 0, "iter_overlapping_windows requires a window and hop of at least one frame");
+
+    OverlapWindowIterator {
+      frames:  self.iter(),
+      window:  Vec::with_capacity(window_size),
+      size:    window_size,
+      hop:     hop_size,
+      started: false
+    }
+  }
+
+  /// Returns an iterator like `iter()`, which keeps the file alive through
+  /// the `Arc` rather than borrowing it.  The iterator is `Send` and
+  /// `'static`, so it can be handed to `std::thread::spawn` or an async
+  /// task.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use std::sync::Arc;
+  /// use std::thread;
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav    = Arc::new(WaveFile::open("./fixtures/test-s24le.wav").unwrap());
+  /// let frames = wav.clone().iter_owned();
+  ///
+  /// let count = thread::spawn(move || frames.count()).join().unwrap();
+  ///
+  /// assert_eq!(count, wav.len());
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn iter_owned(self: Arc<Self>) -> OwnedWaveFileIterator {
+    OwnedWaveFileIterator { file: self, frame: 0 }
+  }
+
+  /// Returns an iterator like `iter()`, which yields each frame together with
+  /// its time offset from the start of the file, as computed by
+  /// `elapsed_at()`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use std::time::Duration;
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// for (time, frame) in wav.iter_with_timestamps().step_by(48000) {
+  ///   println!("{:?}: {:?}", time, frame);
+  /// }
+  ///
+  /// let (time, _) = wav.iter_with_timestamps().nth(24000).unwrap();
+  /// assert_eq!(time, Duration::from_millis(500));
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn iter_with_timestamps(&self) -> TimestampedIterator<'_> {
+    TimestampedIterator { frames: self.iter() }
+  }
+
+  // asks the OS to read ahead the whole mapping (where supported), then
+  // touches every page so that it is actually resident.
+  #[cfg(not(feature = "no_std"))]
+  fn preload(&self) {
+    #[cfg(unix)]
+    {
+      if let Buffer::Mapped(ref mmap) = self.buffer {
+        unsafe {
+          libc::madvise(mmap.as_ptr() as *mut libc::c_void, mmap.len(), libc::MADV_WILLNEED);
+        }
+      }
+    }
+
+    let sum = self.buffer.iter().step_by(4096).fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+    std::hint::black_box(sum);
+  }
+
+  // attaches the path of this file (if known) to an error about its contents.
+  fn in_file(&self, e: WaveError) -> WaveError {
+    #[cfg(not(feature = "no_std"))]
+    {
+      if let Some(ref path) = self.path {
+        return e.with_path(path);
+      }
+    }
+
+    e
+  }
+
+  // the contents of the data chunk, clamped to the end of the file.
+  fn data_bytes(&self) -> &[u8] {
+    let start = cmp::min(self.data_offset as usize, self.buffer.len());
+    let end   = cmp::min(start.saturating_add(self.data_size as usize), self.buffer.len());
+
+    &self.buffer[start..end]
+  }
+
+  fn read_sample<B: ByteOrder>(&self, bytes: &[u8]) -> Option<Frame> {
+    let bps = self.info.bits_per_sample / 8;
+
+    match self.data_format() {
+      Format::PCM       => WaveFileIterator::next_pcm::<B>(bytes, bps),
+      Format::IEEEFloat => WaveFileIterator::next_float::<B>(bytes, bps),
+      _                 => None
+    }
+  }
+
+  fn bytes_per_frame(&self) -> u64 {
+    self.info.channels as u64 * (self.info.bits_per_sample / 8) as u64
+  }
+
+  // `data` holds exactly the bytes declared in the chunk header, so fields
+  // are only read if they fit within it.  Plain PCM chunks may be 16 bytes
+  // long or carry an arbitrary extension, which is ignored; extensible
+  // chunks need at least the 40 bytes including the sub-format GUID.
+  // `offset` is the position of `data` in the file, for error reporting.
+  fn read_format_chunk<B: ByteOrder>(info: &mut WaveInfo, data: &[u8], offset: u64) -> Result<(), WaveError> {
+    let required = match data.len() {
+      n if n >= 2 && Format::decode(B::read_u16(&data[0..2])) == Format::Extensible => 40,
+      _ => 16
+    };
+
+    if data.len() < required {
+      let msg = format!("Format chunk of {} bytes is too short, expected at least {}", data.len(), required);
+      return Err(WaveError::parse_at(msg, offset));
+    }
+
+    info.audio_format = Format::decode(B::read_u16(&data[0..2]));
+
+    info.channels        = B::read_u16(&data[2..4]);
+    info.sample_rate     = B::read_u32(&data[4..8]);
+    info.byte_rate       = B::read_u32(&data[8..12]);
+    info.block_align     = B::read_u16(&data[12..14]);
+    info.bits_per_sample = B::read_u16(&data[14..16]);
+
+    if info.audio_format == Format::Extensible {
+      // any bytes beyond the 22 we understand are skipped.
+      match B::read_u16(&data[16..18]) {
+        x if x >= 22 => {
+          info.valid_bps    = Some(B::read_u16(&data[18..20]));
+          info.channel_mask = Some(B::read_u32(&data[20..24]));
+          info.subformat    = Some(Format::decode(B::read_u16(&data[24..26])));
+        },
+        x => {
+          let msg = format!("Unexpected extension size: {}", x);
+          return Err(WaveError::parse_at(msg, offset + 16));
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  #[cfg(not(feature = "no_std"))]
+  fn read_format_from<R: Read + Seek>(mut reader: R) -> Result<Format, WaveError> {
+    let mut header = Vec::with_capacity(40);
+    reader.by_ref().take(40).read_to_end(&mut header)?;
+
+    if header.len() == 40 && header[0..16] == W64_RIFF && header[24..40] == W64_WAVE {
+      return WaveFile::read_format_as::<LittleEndian, R>(reader, true);
+    }
+
+    if header.len() < 12 {
+      return Err(WaveError::NotAWavFile);
+    }
+
+    let riff_id   = LittleEndian::read_u32(&header[0..4]);
+    let riff_type = LittleEndian::read_u32(&header[8..12]);
+
+    if (riff_id != RIFF && riff_id != RIFX && riff_id != RF64) || riff_type != WAVE {
+      return Err(WaveError::NotAWavFile);
+    }
+
+    reader.seek(SeekFrom::Start(12))?;
+
+    if riff_id == RIFX {
+      WaveFile::read_format_as::<BigEndian, R>(reader, false)
+    } else {
+      WaveFile::read_format_as::<LittleEndian, R>(reader, false)
+    }
+  }
+
+  // reads chunk headers, skipping over each chunk's contents, until the
+  // `fmt ` chunk is found.  Mirrors `ChunkIterator` without reading the
+  // entire file into memory.
+  #[cfg(not(feature = "no_std"))]
+  fn read_format_as<B: ByteOrder, R: Read + Seek>(mut reader: R, wave64: bool) -> Result<Format, WaveError> {
+    let (header_len, align) = if wave64 { (24, 8) } else { (8, 2) };
+    let mut header = [0; 24];
+
+    loop {
+      if let Err(e) = reader.read_exact(&mut header[..header_len]) {
+        return match e.kind() {
+          io::ErrorKind::UnexpectedEof => Err(WaveError::parse("No format chunk found")),
+          _                            => Err(e.into())
+        };
+      }
+
+      let id   = LittleEndian::read_u32(&header[0..4]);
+      let size = if wave64 {
+        LittleEndian::read_u64(&header[16..24]).saturating_sub(24)
+      } else {
+        B::read_u32(&header[4..8]) as u64
+      };
+
+      if id == FMT_ {
+        let offset   = reader.stream_position()?;
+        let mut data = Vec::new();
+        reader.take(size).read_to_end(&mut data)?;
+
+        let mut info = WaveInfo::default();
+        WaveFile::read_format_chunk::<B>(&mut info, &data, offset)?;
+
+        return Ok(info.data_format());
+      }
+
+      let skip = size + (align - size % align) % align;
+      reader.seek(SeekFrom::Current(skip as i64))?;
+    }
+  }
+
+  fn read_chunks(&mut self, options: &OpenOptions) -> Result<(), WaveError> {
+    if self.buffer.len() >= 40 && self.buffer[0..16] == W64_RIFF && self.buffer[24..40] == W64_WAVE {
+      self.wave64 = true;
+      return self.read_chunks_as::<LittleEndian>(options);
+    }
+
+    if self.buffer.len() < 12 {
+      return Err(WaveError::NotAWavFile);
+    }
+
+    let riff_id   = LittleEndian::read_u32(&self.buffer[0..4]);
+    let riff_type = LittleEndian::read_u32(&self.buffer[8..12]);
+
+    if (riff_id != RIFF && riff_id != RIFX && riff_id != RF64) || riff_type != WAVE {
+      return Err(WaveError::NotAWavFile);
+    }
+
+    self.big_endian = riff_id == RIFX;
+    self.rf64       = riff_id == RF64;
+
+    if self.big_endian {
+      self.read_chunks_as::<BigEndian>(options)
+    } else {
+      self.read_chunks_as::<LittleEndian>(options)
+    }
+  }
+
+  // all chunk headers are collected before any of them is interpreted, so
+  // chunks may appear in any order, including after the `data` chunk.
+  fn read_chunks_as<B: ByteOrder>(&mut self, options: &OpenOptions) -> Result<(), WaveError> {
+    let total  = self.buffer.len() as u64;
+    let chunks = ChunkIterator::new(&self.buffer, self.big_endian, self.wave64)
+      .skip_null_padding(options.lenient)
+      .inspect(|chunk| options.report_progress(chunk.offset + chunk.data.len() as u64, total))
+      .collect::<Vec<_>>();
+
+    // the chunk iterator takes the sizes of oversized chunks from `ds64`,
+    // which must be the first chunk of an RF64 file.
+    if self.rf64 && chunks.first().is_none_or(|chunk| chunk.id != DS64 || chunk.data.len() < 24) {
+      return Err(WaveError::parse_at("RF64 file does not start with a valid ds64 chunk", 12));
+    }
+
+    let fmt_offset = match chunks.iter().find(|chunk| chunk.id == FMT_) {
+      Some(chunk) => {
+        WaveFile::read_format_chunk::<B>(&mut self.info, chunk.data, chunk.offset)?;
+        chunk.offset
+      },
+      None => return Err(WaveError::parse("No format chunk found"))
+    };
+
+    match chunks.iter().find(|chunk| chunk.id == DATA) {
+      Some(chunk) => {
+        self.data_offset = chunk.offset;
+        self.data_size   = chunk.size;
+      },
+      None => return Err(WaveError::parse("No data chunk found"))
+    }
+
+    if let Some(chunk) = chunks.iter().find(|chunk| chunk.id == FACT) {
+      if chunk.data.len() < 4 {
+        return Err(WaveError::parse_at("Fact chunk is too short", chunk.offset));
+      }
+
+      self.fact_length = Some(B::read_u32(&chunk.data[0..4]));
+    }
+
+    if let Some(chunk) = chunks.iter().find(|chunk| chunk.id == ACID) {
+      self.acid = Some(AcidChunk::read::<B>(chunk.data).map_err(|e| e.at_offset(chunk.offset))?);
+    }
+
+    if let Some(chunk) = chunks.iter().find(|chunk| chunk.id == INST) {
+      self.instrument = Some(InstrumentChunk::read(chunk.data).map_err(|e| e.at_offset(chunk.offset))?);
+    }
+
+    if let Some(chunk) = chunks.iter().find(|chunk| chunk.id == PEAK) {
+      self.peak = Some(PeakChunk::read::<B>(chunk.data).map_err(|e| e.at_offset(chunk.offset))?);
+    }
+
+    self.validate_format(options, fmt_offset)?;
+
+    let frame_size = self.info.channels as u32 * self.info.bits_per_sample as u32 / 8;
+
+    if frame_size > u16::MAX as u32 {
+      let msg = format!("Frame size of {} bytes does not fit in block_align", frame_size);
+      return Err(WaveError::parse_at(msg, fmt_offset + 12));
+    }
+
+    self.info.total_frames = cmp::min(self.data_size / frame_size as u64, u32::MAX as u64) as u32;
+
+    Ok(())
+  }
+
+  // `fmt_offset` is the position of the `fmt ` chunk data, for error
+  // reporting.
+  fn validate_format(&self, options: &OpenOptions, fmt_offset: u64) -> Result<(), WaveError> {
+    let bps = self.info.bits_per_sample;
+    let expected_byte_rate = self.info.sample_rate as u64 * self.info.block_align as u64;
+
+    // only PCM and IEEE Float data can be decoded; this includes rejecting
+    // an extensible file whose subformat is `Format::Extensible` again.
+    if !matches!(self.data_format(), Format::PCM | Format::IEEEFloat) {
+      let msg = format!("Unexpected format {0:x}", self.data_format().code());
+      Err(WaveError::Unsupported(msg))
+    } else if self.info.channels == 0 {
+      let msg = "No audio channels present in this file (weird, right?)";
+      Err(WaveError::parse_at(msg, fmt_offset + 2))
+    } else if self.info.bits_per_sample < 8 {
+      let msg = format!("Unsupported bits per sample: {} expected at least 8.", bps);
+      Err(WaveError::Unsupported(msg))
+    } else if self.data_format() == Format::PCM && !(bps == 8 || bps == 16 || bps == 24 || bps == 32) {
+      let msg = format!("Unsupported bits per sample for PCM data: {} expected 8/16/24/32.", bps);
+      Err(WaveError::Unsupported(msg))
+    } else if self.data_format() == Format::IEEEFloat && !(bps == 32 || bps == 64) {
+      let msg = format!("Unsupported bits per sample for floating point data: {} expected 32/64.", bps);
+      Err(WaveError::Unsupported(msg))
+    } else if !options.lenient && self.info.byte_rate as u64 != expected_byte_rate {
+      let msg = format!("Byte rate {} does not match sample rate * block align ({}).", self.info.byte_rate, expected_byte_rate);
+      Err(WaveError::InconsistentHeader(msg))
+    } else {
+      Ok(())
+    }
+  }
+}
+
+impl fmt::Debug for WaveFile {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut debug = f.debug_struct("WaveFile");
+
+    #[cfg(not(feature = "no_std"))]
+    debug.field("path", &self.path);
+
+    debug.field("info", &self.info).finish()
+  }
+}
+
+impl<'a> Iterator for WaveFileIterator<'a> {
+  type Item = Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let start = self.base + self.pos;
+    let end   = start + self.file.bytes_per_frame();
+
+    if end > self.end || end > self.file.buffer.len() as u64 {
+      return None;
+    }
+
+    let bytes = &self.file.buffer[start as usize..end as usize];
+
+    let frame = if self.file.big_endian {
+      self.read_frame::<BigEndian>(bytes)
+    } else {
+      self.read_frame::<LittleEndian>(bytes)
+    };
+
+    self.pos = end - self.base;
+
+    frame
+  }
+}
+
+impl<'a> Iterator for FrameEnumerator<'a> {
+  type Item = (usize, Frame);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let index = self.frames.frame_index();
+    self.frames.next().map(|frame| (index, frame))
+  }
+}
+
+impl<'a> Iterator for StepIterator<'a> {
+  type Item = Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let frame = self.frames.next()?;
+    self.frames.skip_frames(self.step - 1);
+
+    Some(frame)
+  }
+}
+
+impl<'a> Iterator for RangedIterator<'a> {
+  type Item = Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let frame = self.frames.next()?;
+    Some(frame.into_iter().map(|sample| sample * self.scale + self.offset).collect())
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.frames.size_hint()
+  }
+}
+
+impl<'a> Iterator for ChannelMaskedIterator<'a> {
+  type Item = Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let mut frame = self.frames.next()?;
+    let mut index = 0;
+
+    // filtering in place reuses the frame's allocation.
+    frame.retain(|_| {
+      let selected = index < 64 && self.mask >> index & 1 == 1;
+      index += 1;
+      selected
+    });
+
+    Some(frame)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.frames.size_hint()
+  }
+}
+
+impl<'a> Iterator for TimestampedIterator<'a> {
+  type Item = (Duration, Frame);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let time = self.frames.file.elapsed_at(self.frames.frame_index());
+    self.frames.next().map(|frame| (time, frame))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.frames.size_hint()
+  }
+}
+
+impl<'a> Iterator for OverlapWindowIterator<'a> {
+  type Item = Vec<Frame>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    // keep the frames shared with the next window, and skip any frames
+    // between windows if they don't overlap.
+    if self.started {
+      if self.hop >= self.size {
+        self.window.clear();
+        self.frames.skip_frames(self.hop - self.size);
+      } else {
+        let hop = cmp::min(self.hop, self.window.len());
+        self.window.drain(..hop);
+      }
+    }
+
+    self.started = true;
+
+    while self.window.len() < self.size {
+      self.window.push(self.frames.next()?);
+    }
+
+    Some(self.window.clone())
+  }
+}
+
+impl Iterator for OwnedWaveFileIterator {
+  type Item = Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let frame = self.file.iter_from(self.frame).next()?;
+    self.frame += 1;
+
+    Some(frame)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let remaining = self.file.len().saturating_sub(self.frame);
+    (remaining, Some(remaining))
+  }
+}
+
+impl PlaybackPosition {
+  /// The index of the frame iteration resumes at.
+  pub fn frame(&self) -> usize {
+    self.frame
+  }
+}
+
+impl<'a> WaveFileIterator<'a> {
+  /// Turns this iterator into one which yields `(index, frame)` pairs, where
+  /// `index` is the position of the frame in the file.  Unlike
+  /// `Iterator::enumerate()`, the index does not start at zero for an
+  /// iterator created with `WaveFile::iter_from()`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// for (index, frame) in wav.iter_from(1000).enumerate_frames().take(10) {
+  ///   println!("{}: {:?}", index, frame);
+  /// }
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn enumerate_frames(self) -> FrameEnumerator<'a> {
+    FrameEnumerator { frames: self }
+  }
+
+  /// Turns this iterator into one which yields the current frame and then
+  /// every `n`th frame after it.  Unlike `Iterator::step_by()`, the frames
+  /// in between are skipped without being decoded, so each step takes the
+  /// same time regardless of `n`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `n` is zero.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// for frame in wav.iter().step_by_frames(512).take(10) {
+  ///   println!("{:?}", frame);
+  /// }
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn step_by_frames(self, n: usize) -> StepIterator<'a> {
+    assert!(n != 0, "step_by_frames requires a step of at least one frame");
+    StepIterator { frames: self, step: n }
+  }
+
+  /// Saves the current position of this iterator, so that iteration can
+  /// be resumed later on with `WaveFile::iter_from_position()` without
+  /// keeping the iterator around.
+  pub fn save_position(&self) -> PlaybackPosition {
+    PlaybackPosition { frame: self.frame_index() }
+  }
+
+  /// Moves this iterator to the frame at index `frame`, forwards or
+  /// backwards, so that it is the next frame to be yielded.  Seeking to
+  /// `len()` is allowed and ends the iteration.
+  ///
+  /// Returns `WaveError::OutOfRange` if `frame` lies beyond the end of the
+  /// file, leaving the position unchanged.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// let mut frames = wav.iter();
+  /// let position   = frames.by_ref().position(|frame| frame[0].abs() > 0.1);
+  ///
+  /// if let Some(index) = position {
+  ///   // rewind a little to include the lead-in.
+  ///   frames.seek_to_frame(index.saturating_sub(10)).unwrap();
+  /// }
+  /// ```
+  pub fn seek_to_frame(&mut self, frame: usize) -> Result<(), WaveError> {
+    if frame > self.file.len() {
+      let msg = format!("Frame {} is beyond the end of the file ({} frames)", frame, self.file.len());
+      return Err(WaveError::OutOfRange(msg));
+    }
+
+    self.pos = cmp::min(frame as u64 * self.file.bytes_per_frame(), self.end - self.base);
+
+    Ok(())
+  }
+
+  fn skip_frames(&mut self, frames: usize) {
+    let pos = self.pos.saturating_add((frames as u64).saturating_mul(self.file.bytes_per_frame()));
+    self.pos = cmp::min(pos, self.end - self.base);
+  }
+
+  fn frame_index(&self) -> usize {
+    (self.pos / self.file.bytes_per_frame()) as usize
+  }
+
+  // returns `None` for sample formats which can't be decoded, ending the
+  // iteration.  Opening a file rejects most of these, but e.g. 40-bit PCM
+  // passes validation.
+  fn read_frame<B: ByteOrder>(&self, bytes: &[u8]) -> Option<Frame> {
+    match self.file.data_format() {
+      Format::PCM => WaveFileIterator::next_pcm::<B>(bytes, self.bytes_per_sample),
+      Format::IEEEFloat => WaveFileIterator::next_float::<B>(bytes, self.bytes_per_sample),
+      format => {
+        debug_assert!(false, "Cannot decode {:?} data", format);
+        None
+      }
+    }
+  }
+
+  fn next_pcm<B: ByteOrder>(bytes: &[u8], bps: u16) -> Option<Frame> {
+    match bps {
+      1 => Some(Self::next_pcm8(bytes)),
+      2 => Some(Self::next_pcm16::<B>(bytes)),
+      3 => Some(Self::next_pcm24::<B>(bytes)),
+      4 => Some(Self::next_pcm32::<B>(bytes)),
+      _ => None
+    }
+  }
+
+  fn next_pcm8(bytes: &[u8]) -> Frame {
+    bytes.iter().map(|&sample| (sample as f32 - 128.0) / 128.0).collect()
+  }
+
+  fn next_pcm16<B: ByteOrder>(bytes: &[u8]) -> Frame {
+    bytes.chunks(2).map(|sample| B::read_i16(sample) as f32 / 32768.0).collect()
+  }
+
+  fn next_pcm24<B: ByteOrder>(bytes: &[u8]) -> Frame {
+    bytes.chunks(3).map(|sample| B::read_i24(sample) as f32 / 8388608.0).collect()
+  }
+
+  fn next_pcm32<B: ByteOrder>(bytes: &[u8]) -> Frame {
+    bytes.chunks(4).map(|sample| B::read_i32(sample) as f32 / 2147483648.0).collect()
+  }
+
+  fn next_float<B: ByteOrder>(bytes: &[u8], bps: u16) -> Option<Frame> {
+    match bps {
+      4 => Some(Self::next_float32::<B>(bytes)),
+      8 => Some(Self::next_float64::<B>(bytes)),
+      _ => None
+    }
+  }
+
+  fn next_float32<B: ByteOrder>(bytes: &[u8]) -> Frame {
+    bytes.chunks(4).map(B::read_f32).collect()
+  }
+
+  fn next_float64<B: ByteOrder>(bytes: &[u8]) -> Frame {
+    bytes.chunks(8).map(|sample| B::read_f64(sample) as f32).collect()
+  }
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_info() {
+  let file = match WaveFile::open("./fixtures/test-s24le.wav") {
+    Ok(f) => f,
+    Err(e) => panic!("Error: {:?}", e)
+  };
+  let info = file.info();
+
+  assert_eq!(info.audio_format,    Format::PCM);
+  assert_eq!(info.channels,        2);
+  assert_eq!(info.sample_rate,     48000);
+  assert_eq!(info.byte_rate,       288000);
+  assert_eq!(info.block_align,     6);
+  assert_eq!(info.bits_per_sample, 24);
+  assert_eq!(info.total_frames,    501888);
+
+  let file = match WaveFile::open("./fixtures/test-u8.wav") {
+    Ok(f) => f,
+    Err(e) => panic!("Error: {:?}", e)
+  };
+  let info = file.info();
+
+  assert_eq!(info.audio_format,    Format::PCM);
+  assert_eq!(info.channels,        2);
+  assert_eq!(info.sample_rate,     48000);
+  assert_eq!(info.byte_rate,       96000);
+  assert_eq!(info.bits_per_sample, 8);
+  assert_eq!(info.block_align,     2);
+  assert_eq!(info.total_frames,    501888);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_iter() {
+  let file = match WaveFile::open("./fixtures/test-s24le.wav") {
+    Ok(f) => f,
+    Err(e) => panic!("Error: {:?}", e)
+  };
+
+  let frames = file.iter().take(2).collect::<Vec<_>>();
+  let expected = [
+    [0.002334237, 0.002334237],
+    [0.0029011965, 0.0029011965]
+  ];
+
+  for i in 0..expected.len() {
+    assert_eq!(frames[i], expected[i]);
+  }
+
+  let frame = file.iter().last().unwrap();
+  let expected = [2.9087067e-05, 2.9087067e-05];
+
+  assert_eq!(frame, expected)
+}
+
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_float_extensible() {
+  let file = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  let info = file.info();
+
+  assert_eq!(info.audio_format,  Format::Extensible);
+  assert_eq!(file.data_format(), Format::IEEEFloat);
+  assert_eq!(file.len(),         501888);
+
+  let frames = file.iter().take(2).collect::<Vec<_>>();
+  let expected = [
+    [0.002334237, 0.002334237],
+    [0.0029011965, 0.0029011965]
+  ];
+
+  for i in 0..expected.len() {
+    assert_eq!(frames[i], expected[i]);
+  }
+
+  assert_eq!(file.speakers().unwrap(),
+             [SpeakerPosition::FrontLeft, SpeakerPosition::FrontRight]);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_data_format_str() {
+  let file = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  assert_eq!(file.data_format_str(),  "IEEE Float");
+  assert_eq!(file.data_format_code(), 3);
+
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert_eq!(file.data_format_str(),  "PCM");
+  assert_eq!(file.data_format_code(), 1);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_channels_info() {
+  let file = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+
+  assert_eq!(file.channels_info(), [
+    ChannelInfo { index: 0, speaker: Some(SpeakerPosition::FrontLeft) },
+    ChannelInfo { index: 1, speaker: Some(SpeakerPosition::FrontRight) }
+  ]);
+
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+
+  assert_eq!(file.channels_info(), [
+    ChannelInfo { index: 0, speaker: None },
+    ChannelInfo { index: 1, speaker: None }
+  ]);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_at() {
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+
+  assert_eq!(file.at(0), file.iter().next());
+  assert_eq!(file.at(1), file.iter().nth(1));
+  assert_eq!(file.at(file.len() - 1), file.iter().last());
+  assert_eq!(file.at(file.len()), None);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_from_vec() {
+  let bytes  = std::fs::read("./fixtures/test-s24le.wav").unwrap();
+  let memory = WaveFile::from_vec(bytes).unwrap();
+  let mapped = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+
+  assert_eq!(memory.len(), mapped.len());
+  assert_eq!(memory.iter().take(10).collect::<Vec<_>>(),
+             mapped.iter().take(10).collect::<Vec<_>>());
+  assert!(WaveFile::from_vec(vec![0; 64]).is_err());
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_clone_into_memory() {
+  let owned = {
+    let mapped = WaveFile::open("./fixtures/test-u8.wav").unwrap();
+    mapped.clone_into_memory()
+  };
+
+  assert_eq!(owned.len(), 501888);
+  assert_eq!(owned.info().bits_per_sample, 8);
+  assert_eq!(owned.iter().count(), 501888);
+}
+
+#[cfg(all(unix, not(feature = "no_std")))]
+#[test]
+fn test_from_raw_fd() {
+  use std::os::unix::io::AsRawFd;
+
+  let file = File::open("./fixtures/test-s24le.wav").unwrap();
+  let wav  = unsafe { WaveFile::from_raw_fd(file.as_raw_fd()).unwrap() };
+
+  assert_eq!(wav.len(), 501888);
+  drop(file);
+  assert_eq!(wav.iter().count(), 501888);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_fact_sample_length() {
+  let file = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  assert_eq!(file.fact_sample_length(), Some(501888));
+
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert_eq!(file.fact_sample_length(), None);
+}
+
+#[test]
+fn test_fact_length_differs_from_data() {
+  let mut wav = build_wave(&pcm_fmt(1, 8000, 16), &[0; 16]);
+  wav.splice(36..36, b"fact\x04\0\0\0\x63\0\0\0".iter().cloned());
+
+  let riff_size = (wav.len() - 8) as u32;
+  wav[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+  let file = WaveFile::from_vec(wav).unwrap();
+
+  assert_eq!(file.fact_sample_length(), Some(99));
+  assert_eq!(file.len(), 8);
+  assert_eq!(file.iter().count(), 8);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_predicates() {
+  let file = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+
+  assert!(file.is_stereo());
+  assert!(!file.is_mono());
+  assert!(!file.is_surround());
+  assert!(file.is_float());
+  assert!(!file.is_pcm());
+  assert!(!file.is_integer());
+  assert!(file.is_lossless());
+
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+
+  assert!(file.is_pcm());
+  assert!(!file.is_float());
+  assert!(file.is_integer());
+  assert!(file.is_lossless());
+}
+
+#[test]
+fn test_info_default() {
+  let info = WaveInfo { sample_rate: 48000, ..Default::default() };
+
+  assert_eq!(info.data_format(), Format::PCM);
+  assert_eq!(info.channels,      2);
+  assert_eq!(info.block_align,   4);
+  assert_eq!(info.byte_rate,     176400);
+  assert!(!info.compatible_with(&WaveInfo::default()));
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_compatible_with() {
+  let s24 = WaveFile::open("./fixtures/test-s24le.wav").unwrap().info();
+  let f32 = WaveFile::open("./fixtures/test-f32le.wav").unwrap().info();
+  let u8  = WaveFile::open("./fixtures/test-u8.wav").unwrap().info();
+
+  assert!(s24.compatible_with(&s24));
+  assert!(!s24.compatible_with(&f32));
+  assert!(s24.compatibility_error(&s24).is_none());
+
+  match s24.compatibility_error(&u8) {
+    Some(WaveError::Incompatible(msg)) => assert_eq!(msg, "8 bits per sample do not match 24"),
+    other => panic!("Expected an Incompatible error, got {:?}", other)
+  }
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_header_bytes() {
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+
+  assert_eq!(file.header_bytes().len(), 44);
+  assert_eq!(&file.header_bytes()[..4], b"RIFF");
+
+  let dump = file.header_hex_dump();
+  let lines = dump.lines().collect::<Vec<_>>();
+
+  assert_eq!(lines.len(), 3);
+  assert_eq!(lines[0], "00000000  52 49 46 46 24 f3 2d 00  57 41 56 45 66 6d 74 20  |RIFF$.-.WAVEfmt |");
+  assert_eq!(lines[2], "00000020  06 00 18 00 64 61 74 61  00 f3 2d 00              |....data..-.|");
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_enumerate_frames() {
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+
+  let frames = file.iter_from(100).enumerate_frames().take(2).collect::<Vec<_>>();
+  assert_eq!(frames[0], (100, file.at(100).unwrap()));
+  assert_eq!(frames[1], (101, file.at(101).unwrap()));
+
+  let (index, _) = file.iter().enumerate_frames().last().unwrap();
+  assert_eq!(index, file.len() - 1);
+  assert_eq!(file.iter_from(file.len()).next(), None);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_error_path() {
+  let err = WaveFile::open("./Cargo.toml").err().unwrap();
+
+  assert_eq!(err.path(), Some(Path::new("./Cargo.toml")));
+  assert_eq!(err.to_string(), "Not a WAV file in './Cargo.toml'");
+
+  match *err.inner() {
+    WaveError::NotAWavFile => (),
+    ref other => panic!("Expected NotAWavFile, got {:?}", other)
+  }
+
+  let err = WaveFile::from_vec(vec![0; 64]).err().unwrap();
+  assert_eq!(err.path(), None);
+  assert_eq!(err.to_string(), "Not a WAV file");
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_into_io_error() {
+  use std::io;
+
+  let err : io::Error = WaveFile::from_vec(vec![0; 64]).err().unwrap().into();
+  assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+  assert_eq!(err.to_string(), "Not a WAV file");
+
+  let err : io::Error = WaveFile::open("./fixtures/missing.wav").err().unwrap().into();
+  assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_chunk_order() {
+  let fmt  = pcm_fmt(1, 8000, 16);
+  let data = [0u8; 8];
+
+  let mut wav = Vec::new();
+  wav.extend_from_slice(b"RIFF\0\0\0\0WAVE");
+  wav.extend_from_slice(b"fact\x04\0\0\0\x04\0\0\0");
+  wav.extend_from_slice(b"JUNK\x03\0\0\0abc\0");
+  wav.extend_from_slice(b"data");
+  wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+  wav.extend_from_slice(&data);
+  wav.extend_from_slice(b"fmt ");
+  wav.extend_from_slice(&(fmt.len() as u32).to_le_bytes());
+  wav.extend_from_slice(&fmt);
+
+  let file = WaveFile::from_vec(wav).unwrap();
+
+  assert_eq!(file.channels(), 1);
+  assert_eq!(file.len(), 4);
+  assert_eq!(file.fact_sample_length(), Some(4));
+  assert_eq!(file.iter().count(), 4);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_acid_info() {
+  let file = WaveFile::open("./fixtures/test-acid.wav").unwrap();
+  let acid = file.acid_info().unwrap();
+
+  assert_eq!(acid.tempo,       120.0);
+  assert_eq!(acid.root_note,   60);
+  assert_eq!(acid.num_beats,   4);
+  assert_eq!(acid.meter_numer, 4);
+  assert_eq!(acid.meter_denom, 4);
+  assert_eq!(file.len(),       64);
+
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert!(file.acid_info().is_none());
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_sample_at() {
+  for name in ["test-s24le.wav", "test-f32le.wav", "test-s16be-rifx.wav", "test-u8.wav"] {
+    let file   = WaveFile::open(format!("./fixtures/{}", name)).unwrap();
+    let frames = file.iter().take(100).collect::<Vec<_>>();
+
+    for (index, frame) in frames.iter().enumerate() {
+      for (channel, &sample) in frame.iter().enumerate() {
+        assert_eq!(file.sample_at(index, channel), Some(sample));
+      }
+    }
+
+    assert_eq!(file.sample_at(0, file.channels() as usize), None);
+    assert_eq!(file.sample_at(file.len(), 0), None);
+    assert!(file.sample_at(file.len() - 1, 0).is_some());
+  }
+}
+
+#[test]
+fn test_peak_chunk() {
+  let samples : [i16; 4] = [8192, -16384, 4096, 0];
+  let data = samples.iter().flat_map(|s| s.to_le_bytes().to_vec()).collect::<Vec<_>>();
+
+  let plain = pcm16_wave(2, &samples);
+  assert!(plain.peak_chunk().is_none());
+  assert_eq!(plain.peak_sample(), 0.5);
+
+  let mut bytes = build_wave(&pcm_fmt(2, 8000, 16), &data);
+  bytes.extend_from_slice(b"PEAK\x18\0\0\0");
+  bytes.extend_from_slice(&1u32.to_le_bytes());
+  bytes.extend_from_slice(&1700000000u32.to_le_bytes());
+
+  for &(value, position) in &[(0.25f32, 0u32), (-0.75, 1)] {
+    bytes.extend_from_slice(&value.to_le_bytes());
+    bytes.extend_from_slice(&position.to_le_bytes());
+  }
+
+  let riff_size = (bytes.len() - 8) as u32;
+  bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+  let file = WaveFile::from_vec(bytes).unwrap();
+  let peak = file.peak_chunk().unwrap();
+
+  assert_eq!(peak.version, 1);
+  assert_eq!(peak.timestamp, 1700000000);
+  assert_eq!(peak.peaks, [PeakInfo { value: 0.25, position: 0 }, PeakInfo { value: -0.75, position: 1 }]);
+
+  // the stored peaks are used as they are, without scanning the samples.
+  assert_eq!(file.peak_sample(), 0.75);
+}
+
+#[test]
+fn test_chunks_after_data() {
+  let mut acid = Vec::new();
+  acid.extend_from_slice(&[0; 12]);
+  acid.extend_from_slice(&8u32.to_le_bytes());
+  acid.extend_from_slice(&[4, 0, 4, 0]);
+  acid.extend_from_slice(&90.0f32.to_le_bytes());
+
+  let fmt = pcm_fmt(1, 8000, 16);
+  let mut wav = b"RIFF\0\0\0\0WAVE".to_vec();
+
+  for &(id, data) in &[(b"data", &[0u8; 8][..]), (b"fmt ", &fmt[..]), (b"acid", &acid[..]), (b"id3 ", &[1, 2, 3][..])] {
+    wav.extend_from_slice(id);
+    wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(data);
+
+    if data.len() % 2 == 1 {
+      wav.push(0);
+    }
+  }
+
+  let riff_size = (wav.len() - 8) as u32;
+  wav[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+  let file = WaveFile::from_vec(wav).unwrap();
+  let ids  = file.chunks_iter().map(|chunk| chunk.id.to_le_bytes()).collect::<Vec<_>>();
+
+  assert_eq!(ids, [*b"data", *b"fmt ", *b"acid", *b"id3 "]);
+  assert_eq!(file.len(), 4);
+  assert_eq!(file.sample_rate(), 8000);
+  assert_eq!(file.acid_info().unwrap().tempo, 90.0);
+  assert_eq!(file.chunks_iter().last().unwrap().data, [1, 2, 3]);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_instrument_info() {
+  let mut bytes = build_wave(&pcm_fmt(1, 44100, 16), &[0; 8]);
+  bytes.extend_from_slice(b"inst");
+  bytes.extend_from_slice(&7u32.to_le_bytes());
+  bytes.extend_from_slice(&[60, 0xfb, 0xfa, 48, 72, 1, 127, 0]);
+
+  let riff_size = (bytes.len() - 8) as u32;
+  bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+  let file = WaveFile::from_vec(bytes.clone()).unwrap();
+  let inst = file.instrument_info().unwrap();
+
+  assert_eq!(*inst, InstrumentChunk {
+    unshifted_note: 60,
+    fine_tune:      -5,
+    gain_db:        -6,
+    low_note:       48,
+    high_note:      72,
+    low_velocity:   1,
+    high_velocity:  127
+  });
+  assert_eq!(file.len(), 4);
+  assert!(WaveFile::open("./fixtures/test-s24le.wav").unwrap().instrument_info().is_none());
+
+  // a truncated chunk is reported as a parse error at its offset.
+  let len = bytes.len();
+  bytes[len - 12..len - 8].copy_from_slice(&4u32.to_le_bytes());
+  bytes.truncate(len - 4);
+  let riff_size = (bytes.len() - 8) as u32;
+  bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+  let e = WaveFile::from_vec(bytes).unwrap_err();
+  assert_eq!(e.byte_offset(), Some(60));
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_copy_to_path() {
+  let dest = temp_path("copy-to-path.wav");
+  let file = WaveFile::open("./fixtures/test-acid.wav").unwrap();
+
+  assert_eq!(file.copy_to_path(&dest).unwrap(), 204);
+
+  let copy = WaveFile::open(&dest).unwrap();
+  assert_eq!(copy.header_bytes(), file.header_bytes());
+  assert_eq!(copy.iter().collect::<Vec<_>>(), file.iter().collect::<Vec<_>>());
+
+  fs::remove_file(&dest).unwrap();
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_format_description() {
+  let file = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  assert_eq!(file.format_description(),
+             "48000 Hz / 32-bit IEEE Float / Stereo (FrontLeft, FrontRight) / 10.456 s");
+
+  let file = WaveFile::open("./fixtures/test-acid.wav").unwrap();
+  assert_eq!(file.format_description(), "8000 Hz / 16-bit PCM / Mono / 0.008 s");
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_print_summary() {
+  let file    = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  let summary = file.print_summary();
+  let lines   = summary.lines().collect::<Vec<_>>();
+
+  assert_eq!(lines, [
+    "Format      : IEEE Float",
+    "Sample Rate : 48000 Hz",
+    "Bit Depth   : 32",
+    "Channels    : 2",
+    "Speakers    : FrontLeft, FrontRight",
+    "Duration    : 00:00:10.456",
+    "Frames      : 501888",
+    "Byte Rate   : 384000 bytes/s",
+    "File Size   : 4015218 bytes"
+  ]);
+
+  let file = WaveFile::open("./fixtures/test-acid.wav").unwrap();
+  assert!(!file.print_summary().contains("Speakers"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json() {
+  let file = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  let json = file.info().to_json_string().unwrap();
+
+  assert!(json.contains("\"sample_rate\":48000"));
+  assert_eq!(WaveInfo::from_json_str(&json).unwrap().to_fmt_chunk_bytes(), file.info().to_fmt_chunk_bytes());
+  assert!(WaveInfo::from_json_str("{}").is_err());
+}
+
+#[test]
+fn test_to_fmt_chunk_bytes() {
+  let original = WaveInfo {
+    audio_format:    Format::Extensible,
+    channels:        6,
+    sample_rate:     48000,
+    byte_rate:       864000,
+    block_align:     18,
+    bits_per_sample: 24,
+    total_frames:    0,
+    valid_bps:       Some(20),
+    channel_mask:    Some(0x3f),
+    subformat:       Some(Format::IEEEFloat)
+  };
+
+  for original in &[original, WaveInfo::default()] {
+    let bytes = original.to_fmt_chunk_bytes();
+    let size  = LittleEndian::read_u32(&bytes[4..8]) as usize;
+
+    assert_eq!(&bytes[..4], b"fmt ");
+    assert_eq!(bytes.len(), 8 + size);
+
+    let mut info = WaveInfo::default();
+    WaveFile::read_format_chunk::<LittleEndian>(&mut info, &bytes[8..], 8).unwrap();
+
+    assert_eq!(info.audio_format,    original.audio_format);
+    assert_eq!(info.channels,        original.channels);
+    assert_eq!(info.sample_rate,     original.sample_rate);
+    assert_eq!(info.byte_rate,       original.byte_rate);
+    assert_eq!(info.block_align,     original.block_align);
+    assert_eq!(info.bits_per_sample, original.bits_per_sample);
+    assert_eq!(info.valid_bps,       original.valid_bps);
+    assert_eq!(info.channel_mask,    original.channel_mask);
+    assert_eq!(info.subformat,       original.subformat);
+  }
+}
+
+#[cfg(all(target_endian = "little", not(feature = "no_std")))]
+#[test]
+fn test_as_f32_slice() {
+  let mut fmt = pcm_fmt(2, 8000, 32);
+  fmt[0] = 3;
+
+  let values : [f32; 6] = [0.0, -0.5, 0.25, 1.0, -1.0, 0.125];
+  let data = values.iter().flat_map(|s| s.to_le_bytes().to_vec()).collect::<Vec<_>>();
+  let file = WaveFile::from_vec(build_wave(&fmt, &data)).unwrap();
+
+  assert_eq!(file.as_f32_slice(), Some(&values[..]));
+  assert_eq!(&file.as_f32_slice().unwrap()[2..4], &file.at(1).unwrap()[..]);
+
+  // the data chunk of this file starts at an odd offset.
+  let file = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+  assert!(file.as_f32_slice().is_none());
+
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert!(file.as_f32_slice().is_none());
+}
+
+#[cfg(all(target_endian = "little", not(feature = "no_std")))]
+#[test]
+fn test_raw_pcm_slice_i16() {
+  let samples : [i16; 6] = [0, 1, -1, 32767, -32768, 1234];
+  let file = pcm16_wave(2, &samples);
+
+  assert_eq!(file.raw_pcm_slice_i16(), Some(&samples[..]));
+
+  let file = WaveFile::open("./fixtures/test-acid.wav").unwrap();
+  assert_eq!(file.raw_pcm_slice_i16().map(|s| s.len()), Some(64));
+
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+  assert!(file.raw_pcm_slice_i16().is_none());
+
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert!(file.raw_pcm_slice_i16().is_none());
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_duration() {
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert_eq!(file.duration(), 10456);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_sample_counts() {
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert_eq!(file.total_samples(), 1003776);
+  assert_eq!(file.sample_count_per_channel(), 501888);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_duration_micros() {
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert_eq!(file.duration_micros(), 10456000);
+
+  let file = WaveFile::from_vec(build_wave(&pcm_fmt(1, 44100, 8), &[0x80; 1001])).unwrap();
+  assert_eq!(file.duration(), 22);
+  assert_eq!(file.duration_micros(), 22698);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_open_with_preload() {
+  let preloaded = WaveFile::open_with_preload("./fixtures/test-s24le.wav").unwrap();
+  let mapped    = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
 
-    info.audio_format = match Format::decode(fmt) {
-      Some(f) => f,
-      None    => {
-        let msg = format!("Unexpected format {0:x}", fmt);
-        return Err(WaveError::ParseError(msg));
-      }
-    };
+  assert_eq!(preloaded.compare(&mapped), CompareResult::Identical);
+  assert!(WaveFile::open_with_preload("./fixtures/missing.wav").is_err());
+}
 
-    info.channels        = cursor.read_u16::<LittleEndian>()?;
-    info.sample_rate     = cursor.read_u32::<LittleEndian>()?;
-    info.byte_rate       = cursor.read_u32::<LittleEndian>()?;
-    info.block_align     = cursor.read_u16::<LittleEndian>()?;
-    info.bits_per_sample = cursor.read_u16::<LittleEndian>()?;
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_open_safe() {
+  let safe   = WaveFile::open_safe("./fixtures/test-s24le.wav").unwrap();
+  let mapped = WaveFile::open_mmap("./fixtures/test-s24le.wav").unwrap();
 
-    if info.audio_format == Format::Extensible {
-      match cursor.read_u16::<LittleEndian>()? {
-        22 => {
-          info.valid_bps    = Some(cursor.read_u16::<LittleEndian>()?);
-          info.channel_mask = Some(cursor.read_u32::<LittleEndian>()?);
-          let subformat          = cursor.read_u16::<LittleEndian>()?;
-          info.subformat    = match Format::decode(subformat) {
-            Some(f) => Some(f),
-            None    => {
-              let msg = format!("Unexpected subformat {0:x}", subformat);
-              return Err(WaveError::ParseError(msg));
-            }
-          };
-          cursor.seek(SeekFrom::Current(14))?;
-        },
-        x => {
-          let msg = format!("Unexpected extension size: {}", x);
-          return Err(WaveError::ParseError(msg));
-        }
-      }
-    }
+  assert!(matches!(safe.buffer, Buffer::Owned(_)));
+  assert!(matches!(mapped.buffer, Buffer::Mapped(_)));
+  assert_eq!(safe.compare(&mapped), CompareResult::Identical);
+  assert_eq!(safe.iter().collect::<Vec<_>>(), mapped.iter().collect::<Vec<_>>());
+  assert_eq!(safe.path(), Some(Path::new("./fixtures/test-s24le.wav")));
 
-    Ok(())
-  }
+  let e = WaveFile::open_safe("./fixtures/missing.wav").unwrap_err();
+  assert_eq!(e.path(), Some(Path::new("./fixtures/missing.wav")));
 
-  fn read_chunks(&mut self) -> Result<(), WaveError> {
-    let mut cursor   = Cursor::new(self.mmap.as_ref());
-    let mut have_fmt = false;
-    let mut chunk_id = cursor.read_u32::<LittleEndian>()?;
-    let mut chunk_size : u32;
+  let opened = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert_eq!(matches!(opened.buffer, Buffer::Owned(_)), cfg!(feature = "safe"));
+}
 
-    cursor.read_u32::<LittleEndian>()?;
+#[cfg(feature = "timeout")]
+#[test]
+fn test_open_with_timeout() {
+  let file   = WaveFile::open_with_timeout("./fixtures/test-s24le.wav", Duration::from_secs(10)).unwrap();
+  let mapped = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  assert_eq!(file.compare(&mapped), CompareResult::Identical);
+
+  let e = WaveFile::open_with_timeout("./fixtures/missing.wav", Duration::from_secs(10)).unwrap_err();
+  assert_eq!(e.path(), Some(Path::new("./fixtures/missing.wav")));
+  assert!(matches!(*e.inner(), WaveError::IoError(ref e) if e.kind() == io::ErrorKind::NotFound));
+
+  // the open may or may not win the race against a zero timeout.
+  if let Err(e) = WaveFile::open_with_timeout("./fixtures/test-s24le.wav", Duration::from_secs(0)) {
+    assert!(matches!(*e.inner(), WaveError::IoError(ref e) if e.kind() == io::ErrorKind::TimedOut));
+  }
+}
 
-    let riff_type = cursor.read_u32::<LittleEndian>()?;
+#[cfg(all(unix, not(feature = "no_std")))]
+#[test]
+fn test_open_mlock() {
+  match WaveFile::open_mlock("./fixtures/test-s16be-rifx.wav") {
+    Ok(locked) => {
+      let mapped = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+
+      assert!(matches!(locked.buffer, Buffer::Mapped(_)));
+      assert_eq!(locked.compare(&mapped), CompareResult::Identical);
+      locked.unlock();
+      locked.unlock();
+    },
+    // the locked memory limit may be too low in restricted environments.
+    Err(e) => assert!(matches!(*e.inner(), WaveError::IoError(_)))
+  }
 
-    if chunk_id != RIFF || riff_type != WAVE {
-      return Err(WaveError::ParseError("Not a Wavefile".into()));
-    }
+  assert!(WaveFile::open_mlock("./fixtures/missing.wav").is_err());
+}
 
-    loop {
-      chunk_id   = cursor.read_u32::<LittleEndian>()?;
-      chunk_size = cursor.read_u32::<LittleEndian>()?;
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_detect_format() {
+  assert_eq!(WaveFile::detect_format("./fixtures/test-f32le.wav").unwrap(), Format::IEEEFloat);
+  assert_eq!(WaveFile::detect_format("./fixtures/test-s16be-rifx.wav").unwrap(), Format::PCM);
+  assert_eq!(WaveFile::detect_format("./fixtures/test-s16le.w64").unwrap(), Format::PCM);
 
-      match chunk_id {
-        FMT_ => {
-          WaveFile::read_format_chunk(&mut self.info, &mut cursor)?;
-          have_fmt = true;
-        },
-        DATA  => {
-          self.data_size = chunk_size;
-          break;
-        },
-        LIST  => { cursor.seek(SeekFrom::Current(chunk_size as i64))?; },
-        FACT  => { cursor.seek(SeekFrom::Current(chunk_size as i64))?; },
-        other => {
-          let msg = format!("Unexpected Chunk ID {0:x}", other);
-          return Err(WaveError::ParseError(msg));
-        }
-      }
-    }
+  let e = WaveFile::detect_format("./Cargo.toml").unwrap_err();
+  assert_eq!(e.path(), Some(Path::new("./Cargo.toml")));
+  assert!(matches!(*e.inner(), WaveError::NotAWavFile));
 
-    if !have_fmt {
-      return Err(WaveError::ParseError("No format chunk found".into()));
-    }
+  let reader = io::Cursor::new(build_wave(&pcm_fmt(2, 8000, 16), &[])[..20].to_vec());
+  assert!(matches!(WaveFile::read_format_from(reader), Err(WaveError::ParseError { .. })));
+}
 
-    self.validate_format()?;
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_open_directory() {
+  let results = WaveFile::open_directory("./fixtures").unwrap();
+  let names   = results.iter()
+    .map(|(path, _)| path.file_name().unwrap().to_str().unwrap())
+    .collect::<Vec<_>>();
+
+  assert_eq!(names, ["test-acid.wav", "test-f32le.wav", "test-s16be-rifx.wav",
+                     "test-s24le.wav", "test-u8.wav"]);
+  assert!(results.iter().all(|(_, result)| result.is_ok()));
+  assert!(WaveFile::open_directory("./fixtures/missing").is_err());
+}
 
-    self.info.total_frames = self.data_size as u32 / (self.info.channels as u32 * self.info.bits_per_sample as u32 / 8 );
-    self.data_offset = cursor.position();
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_is_wav_path() {
+  for name in ["a.wav", "a.WAV", "a.Wav", "a.wave", "a.WAVE", "dir.d/a.wav"] {
+    assert!(is_wav_path(Path::new(name)), "{}", name);
+  }
 
-    Ok(())
+  for name in ["a.w64", "a.wav.bak", "wav", "a.", "a.waves"] {
+    assert!(!is_wav_path(Path::new(name)), "{}", name);
   }
+}
 
-  fn validate_format(&self) -> Result<(), WaveError> {
-    let bps = self.info.bits_per_sample;
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_playback_position() {
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
 
-    if self.info.channels == 0 {
-      let msg = format!("No audio channels present in this file (weird, right?)");
-      Err(WaveError::ParseError(msg))
-    }
-    else if self.info.bits_per_sample < 8 {
-      let msg = format!("Unsupported bits per sample: {} expected at least 8.", bps);
-      Err(WaveError::Unsupported(msg))
-    } else if self.data_format() == Format::IEEEFloat && !(bps == 32 || bps == 64) {
-      let msg = format!("Unsupported bits per sample for floating point data: {} expected 32/64.", bps);
-      Err(WaveError::Unsupported(msg))
-    } else {
-      Ok(())
-    }
-  }
+  let mut frames = file.iter_from(10);
+  frames.next();
+
+  let position = frames.save_position();
+  assert_eq!(position.frame(), 11);
+  assert_eq!(file.iter_from_position(position).next(), file.at(11));
+
+  let start = PlaybackPosition::default();
+  assert_eq!(file.iter_from_position(start).collect::<Vec<_>>(), file.iter().collect::<Vec<_>>());
+
+  let end = file.iter_from(file.len()).save_position();
+  assert_eq!(end.frame(), file.len());
+  assert_eq!(file.iter_from_position(end).next(), None);
 }
 
-impl<'a> Iterator for WaveFileIterator<'a> {
-  type Item = Frame;
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_seek_to_frame() {
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+  let mut frames = file.iter_from(20);
 
-  fn next(&mut self) -> Option<Self::Item> {
-    let mut cursor = Cursor::new(self.file.mmap.as_ref());
+  frames.seek_to_frame(5).unwrap();
+  assert_eq!(frames.next(), file.at(5));
 
-    if cursor.seek(SeekFrom::Start(self.base + self.pos)).is_err() {
-      return None;
-    };
+  frames.seek_to_frame(30).unwrap();
+  assert_eq!(frames.next(), file.at(30));
 
-    if cursor.position() == self.end {
-      return None;
-    }
+  frames.seek_to_frame(file.len()).unwrap();
+  assert_eq!(frames.next(), None);
 
-    // TODO: if the data is in PCM format, we return the original values.
-    // For example, pcm_8 yields values in the range [0, 255], while
-    // pcm_16 yields values from [-32767, 32767].
-    // However, for float data, since we can't return a float value here,
-    // We convert the result to the full range of an i32.
-    // Ideally we should let the caller specify what range they want the
-    // data scaled to, if any;  however, I don't know how to do this without
-    // writing out a million different conversion functions for each case.
-    let (frame, new_pos) = match self.file.data_format() {
-      Format::PCM => WaveFileIterator::next_pcm(
-        &mut cursor,
-        self.file.channels(),
-        self.bytes_per_sample
-      ),
-      Format::IEEEFloat => WaveFileIterator::next_float(
-        &mut cursor,
-        self.file.channels(),
-        self.bytes_per_sample
-      ),
-      _ => unreachable!()
-    };
+  assert!(matches!(frames.seek_to_frame(file.len() + 1), Err(WaveError::OutOfRange(_))));
+  frames.seek_to_frame(0).unwrap();
+  assert_eq!(frames.count(), file.len());
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_iter_in_range() {
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
 
-    self.pos = new_pos - self.base;
+  let unipolar = file.iter_in_range(0.0, 1.0).collect::<Vec<_>>();
+  let integer  = file.iter_in_range(-32768.0, 32768.0).collect::<Vec<_>>();
+  let inverted = file.iter_in_range(1.0, -1.0).collect::<Vec<_>>();
 
-    Some(frame)
+  assert_eq!(unipolar.len(), file.len());
+
+  for (((frame, unipolar), integer), inverted) in file.iter().zip(unipolar).zip(integer).zip(inverted) {
+    for i in 0..frame.len() {
+      assert_eq!(unipolar[i], (frame[i] + 1.0) / 2.0);
+      assert_eq!(integer[i], frame[i] * 32768.0);
+      assert_eq!(inverted[i], -frame[i]);
+    }
   }
 }
 
-impl<'a> WaveFileIterator<'a> {
-  fn next_pcm(cursor: &mut Cursor<&[u8]>, channels: u16, bps: u16) -> (Frame, u64) {
-    match bps {
-      1 => Self::next_pcm8(cursor, channels),
-      2 => Self::next_pcm16(cursor, channels),
-      3 => Self::next_pcm24(cursor, channels),
-      4 => Self::next_pcm32(cursor, channels),
-      _ => unreachable!(),
+#[test]
+fn test_undecodable_sample_size() {
+  for &bps in &[12, 40] {
+    match WaveFile::from_vec(build_wave(&pcm_fmt(1, 8000, bps), &[0; 20])) {
+      Err(WaveError::Unsupported(_)) => (),
+      other => panic!("Expected an Unsupported error, got {:?}", other.err())
     }
   }
+}
 
-  fn next_pcm8(cursor: &mut Cursor<&[u8]>, channels: u16) -> (Frame, u64) {
-    let mut samples : Vec<f32> = Vec::with_capacity(channels as usize);
-
-    for _ in 0..channels {
-      match cursor.read_u8() {
-        Ok(sample) => samples.push((sample as f32 - 128.0) / 128.0),
-        Err(e)     => { panic!("{:?}", e); }
-      }
-    }
+#[test]
+fn test_nested_extensible_format() {
+  let info = WaveInfo {
+    audio_format: Format::Extensible,
+    channel_mask: Some(0x3),
+    subformat:    Some(Format::Extensible),
+    ..WaveInfo::default()
+  };
+  let fmt = info.to_fmt_chunk_bytes().split_off(8);
 
-    (samples, cursor.position())
+  match WaveFile::from_vec(build_wave(&fmt, &[0; 16])) {
+    Err(WaveError::Unsupported(_)) => (),
+    other => panic!("Expected an Unsupported error, got {:?}", other.err())
   }
+}
 
-  fn next_pcm16(cursor: &mut Cursor<&[u8]>, channels: u16) -> (Frame, u64) {
-    let mut samples : Vec<f32> = Vec::with_capacity(channels as usize);
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_iter_channels() {
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
 
-    for _ in 0..channels {
-      match cursor.read_i16::<LittleEndian>() {
-        Ok(sample) => samples.push(sample as f32 / 32768.0),
-        Err(e)     => { panic!("{:?}", e); }
-      }
-    }
+  let right = file.iter_channels(0b10).collect::<Vec<_>>();
+  let both  = file.iter_channels(u64::MAX).collect::<Vec<_>>();
 
-    (samples, cursor.position())
-  }
+  assert_eq!(right.len(), file.len());
+  assert!(right.iter().zip(file.iter()).all(|(right, frame)| *right == [frame[1]]));
+  assert_eq!(both, file.iter().collect::<Vec<_>>());
+  assert!(file.iter_channels(0b100).all(|frame| frame.is_empty()));
+}
 
-  fn next_pcm24(cursor: &mut Cursor<&[u8]>, channels: u16) -> (Frame, u64) {
-    let mut samples : Vec<f32> = Vec::with_capacity(channels as usize);
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_iter_with_timestamps() {
+  let file   = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+  let frames = file.iter_with_timestamps().collect::<Vec<_>>();
+
+  assert_eq!(frames.len(), file.len());
+  assert_eq!(frames[0].0, Duration::from_secs(0));
+  assert_eq!(frames[4].0, Duration::from_micros(500));
+  assert_eq!(frames[31].0, file.elapsed_at(31));
+  assert!(frames.iter().zip(file.iter()).all(|((_, a), b)| *a == b));
+  assert_eq!(file.iter_with_timestamps().size_hint(), file.iter().size_hint());
+}
 
-    for _ in 0..channels {
-      match cursor.read_i24::<LittleEndian>() {
-        Ok(sample) => samples.push(sample as f32 / 8388608.0),
-        Err(e)     => { panic!("{:?}", e); }
-      }
-    }
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_iter_overlapping_windows() {
+  let file   = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+  let frames = file.iter().collect::<Vec<_>>();
 
-    (samples, cursor.position())
-  }
+  let windows = file.iter_overlapping_windows(8, 4).collect::<Vec<_>>();
+  assert_eq!(windows.len(), 7);
+  assert!(windows.iter().enumerate().all(|(i, window)| window[..] == frames[i * 4..i * 4 + 8]));
 
-  fn next_pcm32(cursor: &mut Cursor<&[u8]>, channels: u16) -> (Frame, u64) {
-    let mut samples : Vec<f32> = Vec::with_capacity(channels as usize);
+  let windows = file.iter_overlapping_windows(5, 10).collect::<Vec<_>>();
+  assert_eq!(windows.len(), 3);
+  assert_eq!(windows[2][..], frames[20..25]);
 
-    for _ in 0..channels {
-      match cursor.read_i32::<LittleEndian>() {
-        Ok(sample) => samples.push(sample as f32 / 2147483648.0),
-        Err(e)     => { panic!("{:?}", e); }
-      }
-    }
+  assert_eq!(file.iter_overlapping_windows(32, 1).count(), 1);
+  assert_eq!(file.iter_overlapping_windows(33, 1).count(), 0);
+}
 
-    (samples, cursor.position())
-  }
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_iter_owned() {
+  let file   = Arc::new(WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap());
+  let frames = file.clone().iter_owned();
 
-  fn next_float(cursor: &mut Cursor<&[u8]>, channels: u16, bps: u16) -> (Frame, u64) {
-    match bps {
-      4 => Self::next_float32(cursor, channels),
-      8 => Self::next_float64(cursor, channels),
-      _ => unreachable!(),
-    }
-  }
+  assert_eq!(frames.size_hint(), (file.len(), Some(file.len())));
+
+  let owned = std::thread::spawn(move || frames.collect::<Vec<_>>()).join().unwrap();
+  assert_eq!(owned, file.iter().collect::<Vec<_>>());
+}
 
-  fn next_float32(cursor: &mut Cursor<&[u8]>, channels: u16) -> (Frame, u64) {
-    let mut samples : Vec<f32> = Vec::with_capacity(channels as usize);
+// `WaveFile` is `Send` and `Sync` because all of its fields are, including
+// the read-only `Mmap`; this fails to compile if that ever changes.
+#[test]
+fn test_send_sync() {
+  fn assert_send_sync<T: Send + Sync>() {}
+  fn assert_send<T: Send + 'static>() {}
 
-    for _ in 0..channels {
-      match cursor.read_f32::<LittleEndian>() {
-        Ok(sample) => {
-          samples.push(sample as f32);
-        },
-        Err(e)     => { panic!("{:?}", e); }
-      }
-    }
+  assert_send_sync::<WaveFile>();
+  assert_send::<OwnedWaveFileIterator>();
+}
 
-    (samples, cursor.position())
-  }
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_step_by_frames() {
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
 
-  fn next_float64(cursor: &mut Cursor<&[u8]>, channels: u16) -> (Frame, u64) {
-    let mut samples : Vec<f32> = Vec::with_capacity(channels as usize);
+  let stepped  = file.iter().step_by_frames(5).collect::<Vec<_>>();
+  let expected = file.iter().step_by(5).collect::<Vec<_>>();
+  assert_eq!(stepped.len(), 7);
+  assert_eq!(stepped, expected);
 
-    for _ in 0..channels {
-      match cursor.read_f64::<LittleEndian>() {
-        Ok(sample) => {
-          samples.push(sample as f32);
-        },
-        Err(e)     => { panic!("{:?}", e); }
-      }
-    }
+  assert_eq!(file.iter().step_by_frames(1).count(), 32);
+  assert_eq!(file.iter().step_by_frames(100).count(), 1);
+  assert_eq!(file.iter_from(30).step_by_frames(2).collect::<Vec<_>>(), [file.at(30).unwrap()]);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_debug() {
+  let file  = WaveFile::open("./fixtures/test-acid.wav").unwrap();
+  let debug = format!("{:?}", file);
+
+  assert!(debug.starts_with("WaveFile { path: Some(\"./fixtures/test-acid.wav\"), info: WaveInfo {"));
+
+  let file = pcm16_wave(1, &[0; 4]);
+  assert!(format!("{:?}", file).starts_with("WaveFile { path: None, info: WaveInfo {"));
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_errors_include_path() {
+  let file = WaveFile::open("./fixtures/test-acid.wav").unwrap();
+  assert_eq!(file.path(), Some(Path::new("./fixtures/test-acid.wav")));
+
+  let err = file.channel_correlation().err().unwrap();
+  assert_eq!(err.path(), Some(Path::new("./fixtures/test-acid.wav")));
+  assert!(err.to_string().contains("in './fixtures/test-acid.wav'"));
+
+  let file = pcm16_wave(1, &[0; 4]);
+  assert_eq!(file.path(), None);
+  assert_eq!(file.channel_correlation().err().unwrap().path(), None);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_bitrate() {
+  let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+
+  assert_eq!(file.bitrate_bps(), 2304000);
+  assert_eq!(file.bitrate_kbps(), 2304.0);
+
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+  assert_eq!(file.bitrate_bps(), 256000);
+}
+
+#[test]
+fn test_elapsed_at() {
+  let file = WaveFile::from_vec(build_wave(&pcm_fmt(1, 8000, 8), &[0x80; 8000])).unwrap();
+
+  assert_eq!(file.elapsed_at(0), Duration::from_secs(0));
+  assert_eq!(file.elapsed_at(4000), Duration::from_millis(500));
+  assert_eq!(file.elapsed_at(1), Duration::from_micros(125));
+
+  assert_eq!(file.frame_at_time(Duration::from_micros(125)), 1);
+  assert_eq!(file.frame_at_time(Duration::from_micros(190)), 2);
+  assert_eq!(file.frame_at_time(Duration::from_secs(5)), 7999);
 
-    (samples, cursor.position())
+  for &frame in &[0, 1, 1234, 7999] {
+    assert_eq!(file.frame_at_time(file.elapsed_at(frame)), frame);
   }
 }
 
 #[test]
-fn test_info() {
-  let file = match WaveFile::open("./fixtures/test-s24le.wav") {
-    Ok(f) => f,
-    Err(e) => panic!("Error: {:?}", e)
-  };
+fn test_format_decode() {
+  assert_eq!(Format::decode(1),      Format::PCM);
+  assert_eq!(Format::decode(3),      Format::IEEEFloat);
+  assert_eq!(Format::decode(0xfffe), Format::Extensible);
+  assert_eq!(Format::decode(2),      Format::Unknown(2));
+  assert_eq!(Format::Unknown(2).code(), 2);
+}
+
+#[test]
+fn test_format_names() {
+  assert_eq!(Format::IEEEFloat.name(),        "IEEE Float");
+  assert_eq!(Format::Unknown(6).name(),       "Unknown");
+  assert_eq!(Format::Unknown(6).codec_name(), "ITU G.711 A-law");
+  assert_eq!(Format::PCM.mime_type(),         "audio/wav");
+  assert_eq!(Format::PCM.to_string(),         "PCM");
+  assert_eq!(Format::Unknown(6).to_string(),  "Unknown (0x0006)");
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_rifx() {
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
   let info = file.info();
 
+  assert!(file.is_big_endian());
   assert_eq!(info.audio_format,    Format::PCM);
   assert_eq!(info.channels,        2);
-  assert_eq!(info.sample_rate,     48000);
-  assert_eq!(info.byte_rate,       288000);
-  assert_eq!(info.block_align,     6);
-  assert_eq!(info.bits_per_sample, 24);
-  assert_eq!(info.total_frames,    501888);
+  assert_eq!(info.sample_rate,     8000);
+  assert_eq!(info.byte_rate,       32000);
+  assert_eq!(info.block_align,     4);
+  assert_eq!(info.bits_per_sample, 16);
+  assert_eq!(info.total_frames,    32);
 
-  let file = match WaveFile::open("./fixtures/test-u8.wav") {
-    Ok(f) => f,
-    Err(e) => panic!("Error: {:?}", e)
-  };
+  let frames = file.iter().take(2).collect::<Vec<_>>();
+  assert_eq!(frames[0], [0.0, 0.0]);
+  assert_eq!(frames[1], [0.03125, -0.03125]);
+  assert_eq!(file.iter().count(), 32);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_wave64() {
+  let file = WaveFile::open("./fixtures/test-s16le.w64").unwrap();
   let info = file.info();
 
+  assert!(file.is_wave64());
+  assert!(!file.is_big_endian());
   assert_eq!(info.audio_format,    Format::PCM);
-  assert_eq!(info.channels,        2);
-  assert_eq!(info.sample_rate,     48000);
-  assert_eq!(info.byte_rate,       96000);
-  assert_eq!(info.bits_per_sample, 8);
-  assert_eq!(info.block_align,     2);
-  assert_eq!(info.total_frames,    501888);
+  assert_eq!(info.channels,        1);
+  assert_eq!(info.sample_rate,     8000);
+  assert_eq!(info.bits_per_sample, 16);
+  assert_eq!(info.total_frames,    20);
+
+  let frames = file.iter().collect::<Vec<_>>();
+  assert_eq!(frames.len(), 20);
+  assert_eq!(frames[0], [0.0]);
+  assert_eq!(frames[2], [0.015625]);
+}
+
+// Builds an RF64 file whose `data` chunk size is only given in `ds64`,
+// followed by a `LIST` chunk.
+#[cfg(all(test, not(feature = "no_std")))]
+fn build_rf64(fmt: &[u8], data: &[u8]) -> Vec<u8> {
+  let mut bytes = Vec::new();
+  bytes.extend_from_slice(b"RF64");
+  bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+  bytes.extend_from_slice(b"WAVEds64");
+  bytes.extend_from_slice(&28u32.to_le_bytes());
+  bytes.extend_from_slice(&(60 + fmt.len() as u64 + data.len() as u64 + 12).to_le_bytes());
+  bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+  bytes.extend_from_slice(&0u64.to_le_bytes());
+  bytes.extend_from_slice(&0u32.to_le_bytes());
+  bytes.extend_from_slice(b"fmt ");
+  bytes.extend_from_slice(&(fmt.len() as u32).to_le_bytes());
+  bytes.extend_from_slice(fmt);
+  bytes.extend_from_slice(b"data");
+  bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+  bytes.extend_from_slice(data);
+  bytes.extend_from_slice(b"LIST\x04\0\0\0INFO");
+  bytes
 }
 
+#[cfg(not(feature = "no_std"))]
 #[test]
-fn test_iter() {
-  let file = match WaveFile::open("./fixtures/test-s24le.wav") {
-    Ok(f) => f,
-    Err(e) => panic!("Error: {:?}", e)
-  };
+fn test_rf64() {
+  let data = (0..20i16).flat_map(|i| (i * 256).to_le_bytes().to_vec()).collect::<Vec<_>>();
+  let file = WaveFile::from_vec(build_rf64(&pcm_fmt(1, 8000, 16), &data)).unwrap();
 
-  let frames = file.iter().take(2).collect::<Vec<_>>();
-  let expected = vec![
-    [0.002334237, 0.002334237],
-    [0.0029011965, 0.0029011965]
-  ];
+  assert!(file.is_rf64());
+  assert!(!file.is_big_endian());
+  assert_eq!(file.len(), 20);
+  assert_eq!(file.iter().count(), 20);
+  assert_eq!(file.at(2), Some(vec![0.015625]));
 
-  for i in 0..expected.len() {
-    assert_eq!(frames[i], expected[i]);
+  let ids = file.chunks_iter().map(|chunk| chunk.id).collect::<Vec<_>>();
+  assert_eq!(ids, [DS64, FMT_, DATA, 0x5453494c]);
+
+  assert!(!WaveFile::open("./fixtures/test-s24le.wav").unwrap().is_rf64());
+
+  // without ds64 the size of the data chunk is unknown.
+  let mut bytes = build_rf64(&pcm_fmt(1, 8000, 16), &data);
+  bytes[12..16].copy_from_slice(b"JUNK");
+
+  match WaveFile::from_vec(bytes) {
+    Err(WaveError::ParseError { byte_offset: Some(12), .. }) => (),
+    other => panic!("Expected a ParseError, got {:?}", other.err())
   }
 
-  let frame = file.iter().last().unwrap();
-  let expected = [2.9087067e-05, 2.9087067e-05];
+  // a data size beyond the end of the file is read up to the end.
+  let mut bytes = build_rf64(&pcm_fmt(1, 8000, 16), &data);
+  bytes[28..36].copy_from_slice(&u64::MAX.to_le_bytes());
 
-  assert_eq!(frame, expected)
+  let file = WaveFile::from_vec(bytes).unwrap();
+  assert_eq!(file.len(), u32::MAX as usize);
+  assert_eq!(file.chunks_iter().count(), 3);
+  assert_eq!(file.iter().count(), 26);
+}
+
+#[cfg(feature = "sha2")]
+#[test]
+fn test_fingerprint() {
+  use std::fs;
+
+  let original = fs::read("./fixtures/test-s16be-rifx.wav").unwrap();
+
+  // same audio, with an extra (empty) LIST chunk before the data chunk.
+  let mut tagged = original.clone();
+  tagged.splice(0x24..0x24, b"LIST\0\0\0\x04INFO".iter().cloned());
+
+  // same layout, with a single sample byte changed.
+  let mut altered = original.clone();
+  let last = altered.len() - 1;
+  altered[last] ^= 1;
+
+  let original = WaveFile::from_vec(original).unwrap();
+  let tagged   = WaveFile::from_vec(tagged).unwrap();
+  let altered  = WaveFile::from_vec(altered).unwrap();
+
+  assert_eq!(original.fingerprint(), tagged.fingerprint());
+  assert_ne!(original.fingerprint(), altered.fingerprint());
+}
+
+// Builds a minimal wavefile in memory from a `fmt ` chunk body and raw
+// sample data.
+#[cfg(test)]
+fn build_wave(fmt: &[u8], data: &[u8]) -> Vec<u8> {
+  let mut bytes = Vec::new();
+  bytes.extend_from_slice(b"RIFF");
+  bytes.extend_from_slice(&((20 + fmt.len() + data.len()) as u32).to_le_bytes());
+  bytes.extend_from_slice(b"WAVEfmt ");
+  bytes.extend_from_slice(&(fmt.len() as u32).to_le_bytes());
+  bytes.extend_from_slice(fmt);
+  bytes.extend_from_slice(b"data");
+  bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+  bytes.extend_from_slice(data);
+  bytes
+}
+
+// Builds a 16 byte `fmt ` chunk body for PCM data.
+#[cfg(test)]
+fn pcm_fmt(channels: u16, sample_rate: u32, bits_per_sample: u16) -> Vec<u8> {
+  let block_align = (channels as u32 * bits_per_sample as u32 / 8) as u16;
+  let mut fmt = Vec::new();
+  fmt.extend_from_slice(&1u16.to_le_bytes());
+  fmt.extend_from_slice(&channels.to_le_bytes());
+  fmt.extend_from_slice(&sample_rate.to_le_bytes());
+  fmt.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+  fmt.extend_from_slice(&block_align.to_le_bytes());
+  fmt.extend_from_slice(&bits_per_sample.to_le_bytes());
+  fmt
+}
+
+// Builds a 16-bit PCM wavefile at 8000 Hz from interleaved `samples`.
+#[cfg(test)]
+fn pcm16_wave(channels: u16, samples: &[i16]) -> WaveFile {
+  let data = samples.iter().flat_map(|s| s.to_le_bytes().to_vec()).collect::<Vec<_>>();
+  WaveFile::from_vec(build_wave(&pcm_fmt(channels, 8000, 16), &data)).unwrap()
+}
+
+// Builds a mono 16-bit PCM wavefile holding a sine tone at half of full scale.
+#[cfg(test)]
+fn sine_wave(sample_rate: u32, frequency: f64, frames: usize) -> WaveFile {
+  use core::f64::consts::PI;
+
+  let data = (0..frames).flat_map(|i| {
+    let phase = 2.0 * PI * frequency * i as f64 / sample_rate as f64;
+    ((phase.sin() * 16384.0) as i16).to_le_bytes().to_vec()
+  }).collect::<Vec<_>>();
+
+  WaveFile::from_vec(build_wave(&pcm_fmt(1, sample_rate, 16), &data)).unwrap()
 }
 
+// A path in the temporary directory for a test to write `name` to.  The
+// process id keeps concurrent test runs from clobbering each other's files.
+#[cfg(all(test, not(feature = "no_std")))]
+fn temp_path(name: &str) -> PathBuf {
+  std::env::temp_dir().join(format!("wavefile-{}-{}", std::process::id(), name))
+}
 
+#[cfg(not(feature = "no_std"))]
 #[test]
-fn test_float_extensible() {
-  let file = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
-  let info = file.info();
+fn test_format_chunk_sizes() {
+  let mut fmt = pcm_fmt(1, 8000, 16);
+
+  // 16 byte chunk without cbSize, 18 byte chunk with an empty extension,
+  // odd sized chunk with a padding byte and a non-empty extension.
+  for extension in &[&[][..], &[0, 0], &[1, 0, 0xff], &[2, 0, 0xff, 0xff]] {
+    fmt.truncate(16);
+    fmt.extend_from_slice(extension);
+
+    let mut bytes = build_wave(&fmt, &[1, 0, 2, 0]);
+
+    if fmt.len() % 2 == 1 {
+      bytes.insert(20 + fmt.len(), 0);
+    }
+
+    let file = WaveFile::from_vec(bytes).unwrap();
+
+    assert_eq!(file.channels(), 1);
+    assert_eq!(file.bits_per_sample(), 16);
+    assert_eq!(file.len(), 2);
+  }
+
+  // extensible chunk with two bytes more than the standard extension.
+  let mut fmt = WaveFile::open("./fixtures/test-f32le.wav").unwrap().info.to_fmt_chunk_bytes().split_off(8);
+  fmt[16] = 24;
+  fmt.extend_from_slice(&[0xff, 0xff]);
+
+  let file = WaveFile::from_vec(build_wave(&fmt, &[0; 16])).unwrap();
 
-  assert_eq!(info.audio_format,  Format::Extensible);
   assert_eq!(file.data_format(), Format::IEEEFloat);
-  assert_eq!(file.len(),         501888);
+  assert_eq!(file.len(), 2);
+}
 
-  let frames = file.iter().take(2).collect::<Vec<_>>();
-  let expected = vec![
-    [0.002334237, 0.002334237],
-    [0.0029011965, 0.0029011965]
-  ];
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_format_chunk_too_short() {
+  let expect_parse_error = |fmt: &[u8]| {
+    match WaveFile::from_vec(build_wave(fmt, &[0; 4])) {
+      Err(WaveError::ParseError { ref message, byte_offset: Some(20) }) if message.contains("too short") => (),
+      other => panic!("Expected a ParseError, got {:?}", other.err())
+    }
+  };
 
-  for i in 0..expected.len() {
-    assert_eq!(frames[i], expected[i]);
+  expect_parse_error(&pcm_fmt(1, 8000, 16)[..4]);
+  expect_parse_error(&pcm_fmt(1, 8000, 16)[..15]);
+
+  // an extensible chunk cut off before the end of the sub-format GUID.
+  let fmt = WaveFile::open("./fixtures/test-f32le.wav").unwrap().info.to_fmt_chunk_bytes().split_off(8);
+  expect_parse_error(&fmt[..26]);
+  expect_parse_error(&fmt[..39]);
+  assert!(WaveFile::from_vec(build_wave(&fmt, &[0; 8])).is_ok());
+}
+
+#[test]
+fn test_frame_size_overflow() {
+  let wav = build_wave(&pcm_fmt(65535, 8000, 32), &[0; 16]);
+
+  match WaveFile::from_vec(wav) {
+    Err(WaveError::ParseError { .. }) => (),
+    other => panic!("Expected a ParseError, got {:?}", other.err())
   }
 
-  assert_eq!(file.speakers().unwrap(),
-             [SpeakerPosition::FrontLeft, SpeakerPosition::FrontRight]);
+  let wav = build_wave(&pcm_fmt(2, 8000, 16), &[0; 16]);
+  assert_eq!(WaveFile::from_vec(wav).unwrap().len(), 4);
 }
 
 #[test]
-fn test_duration() {
+fn test_inconsistent_byte_rate() {
+  let mut fmt = pcm_fmt(2, 8000, 16);
+  fmt[8] ^= 0xff;
+
+  let wav = build_wave(&fmt, &[0; 16]);
+
+  match WaveFile::from_vec(wav.clone()) {
+    Err(WaveError::InconsistentHeader(_)) => (),
+    other => panic!("Expected an InconsistentHeader error, got {:?}", other.err())
+  }
+
+  let file = OpenOptions::new().lenient(true).open_vec(wav).unwrap();
+  assert_eq!(file.len(), 4);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_detect_sample_rate_heuristic() {
   let file = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
-  assert_eq!(file.duration(), 10456);
+  assert_eq!(file.detect_sample_rate_heuristic(), Some(48000));
+
+  let open = |fmt: &[u8]| OpenOptions::new().lenient(true).open_vec(build_wave(fmt, &[0; 16])).unwrap();
+
+  // a zeroed sample rate is recovered from the byte rate.
+  let mut fmt = pcm_fmt(2, 44100, 16);
+  fmt[4..8].copy_from_slice(&0u32.to_le_bytes());
+  assert_eq!(open(&fmt).detect_sample_rate_heuristic(), Some(44100));
+
+  // as is a wrong one.
+  fmt[4..8].copy_from_slice(&4410u32.to_le_bytes());
+  assert_eq!(open(&fmt).detect_sample_rate_heuristic(), Some(44100));
+
+  // but not if the byte rate doesn't give a standard rate either.
+  fmt[8..12].copy_from_slice(&12345u32.to_le_bytes());
+  assert_eq!(open(&fmt).detect_sample_rate_heuristic(), None);
+}
+
+#[test]
+fn test_repair_header() {
+  // truncated: 4 frames declared, but only 2.5 present.
+  let mut wav = build_wave(&pcm_fmt(2, 8000, 16), &[0; 16]);
+  wav.truncate(44 + 10);
+
+  let file = WaveFile::from_vec(wav).unwrap();
+  assert_eq!(file.len(), 4);
+  assert_eq!(file.repair_header().total_frames, 2);
+
+  // streamed: the data chunk size was never filled in.
+  let mut wav = build_wave(&pcm_fmt(2, 8000, 16), &[0; 16]);
+  wav[40..44].copy_from_slice(&[0; 4]);
+
+  let file = WaveFile::from_vec(wav).unwrap();
+  let info = file.repair_header();
+
+  assert_eq!(file.len(), 0);
+  assert_eq!(info.total_frames, 4);
+  assert_eq!(info.sample_rate, 8000);
+}
+
+#[test]
+fn test_null_padding_between_chunks() {
+  let fmt = pcm_fmt(1, 8000, 16);
+
+  let mut wav = Vec::new();
+  wav.extend_from_slice(b"RIFF\0\0\0\0WAVEfmt ");
+  wav.extend_from_slice(&(fmt.len() as u32).to_le_bytes());
+  wav.extend_from_slice(&fmt);
+  wav.extend_from_slice(&[0; 6]);
+  wav.extend_from_slice(b"data\x08\0\0\0");
+  wav.extend_from_slice(&[0; 8]);
+
+  match WaveFile::from_vec(wav.clone()) {
+    Err(WaveError::ParseError { .. }) => (),
+    other => panic!("Expected a ParseError, got {:?}", other.err())
+  }
+
+  let file = OpenOptions::new().lenient(true).open_vec(wav).unwrap();
+  assert_eq!(file.len(), 4);
+  assert_eq!(file.chunks_iter().count(), 2);
+}
+
+// runs with the `no_std` feature as well, where files can't be opened by path.
+#[test]
+fn test_from_vec_iter() {
+  let file = pcm16_wave(2, &[0, 16384, -16384, 8192]);
+
+  assert_eq!(file.len(), 2);
+  assert_eq!(file.chunks_iter().count(), 2);
+  assert_eq!(file.iter().collect::<Vec<_>>(), [[0.0, 0.5], [-0.5, 0.25]]);
+  assert_eq!(file.iter_from(1).next(), file.at(1));
 }
@@ -0,0 +1,326 @@
+#[cfg(feature = "no_std")]
+use alloc::string::String;
+#[cfg(feature = "no_std")]
+use alloc::vec::Vec;
+use byteorder::{ByteOrder, BigEndian, LittleEndian};
+
+use super::{ChunkIterator, WaveError, WaveFile};
+
+const CUE_ : u32 = 0x20657563;
+const LIST : u32 = 0x5453494c;
+const ADTL : u32 = 0x6c746461;
+const LABL : u32 = 0x6c62616c;
+const NOTE : u32 = 0x65746f6e;
+
+/// Loop and tempo information from an `acid` chunk, as written by Acid and
+/// other loop based tools.
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct AcidChunk {
+  /// Bit flags: `0x01` one-shot, `0x02` root note set, `0x04` stretch,
+  /// `0x08` disk based, `0x10` high octave.
+  pub type_flags:    u32,
+  /// MIDI root note, only meaningful if bit `0x02` of `type_flags` is set.
+  pub root_note:     u16,
+  pub unknown:       u16,
+  pub unknown_float: f32,
+  /// Number of beats in the loop.
+  pub num_beats:     u32,
+  /// Denominator of the time signature.
+  pub meter_denom:   u16,
+  /// Numerator of the time signature.
+  pub meter_numer:   u16,
+  /// Tempo in beats per minute.
+  pub tempo:         f32
+}
+
+impl AcidChunk {
+  pub(crate) fn read<B: ByteOrder>(data: &[u8]) -> Result<AcidChunk, WaveError> {
+    if data.len() < 24 {
+      return Err(WaveError::parse("Acid chunk is too short"));
+    }
+
+    Ok(AcidChunk {
+      type_flags:    B::read_u32(&data[0..4]),
+      root_note:     B::read_u16(&data[4..6]),
+      unknown:       B::read_u16(&data[6..8]),
+      unknown_float: B::read_f32(&data[8..12]),
+      num_beats:     B::read_u32(&data[12..16]),
+      meter_denom:   B::read_u16(&data[16..18]),
+      meter_numer:   B::read_u16(&data[18..20]),
+      tempo:         B::read_f32(&data[20..24])
+    })
+  }
+}
+
+/// Sampler settings from an `inst` chunk, describing which MIDI notes and
+/// velocities should trigger the sample and how to tune it.
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct InstrumentChunk {
+  /// MIDI note at which the sample plays back unshifted.
+  pub unshifted_note: u8,
+  /// Pitch adjustment in cents, from -50 to 50.
+  pub fine_tune:      i8,
+  /// Gain in decibels to apply on playback.
+  pub gain_db:        i8,
+  /// Lowest MIDI note of the key range.
+  pub low_note:       u8,
+  /// Highest MIDI note of the key range.
+  pub high_note:      u8,
+  /// Lowest MIDI velocity of the velocity range.
+  pub low_velocity:   u8,
+  /// Highest MIDI velocity of the velocity range.
+  pub high_velocity:  u8
+}
+
+impl InstrumentChunk {
+  pub(crate) fn read(data: &[u8]) -> Result<InstrumentChunk, WaveError> {
+    if data.len() < 7 {
+      return Err(WaveError::parse("Instrument chunk is too short"));
+    }
+
+    Ok(InstrumentChunk {
+      unshifted_note: data[0],
+      fine_tune:      data[1] as i8,
+      gain_db:        data[2] as i8,
+      low_note:       data[3],
+      high_note:      data[4],
+      low_velocity:   data[5],
+      high_velocity:  data[6]
+    })
+  }
+}
+
+/// Pre-computed peak levels from a `PEAK` chunk, as written by some DAWs.
+/// See `WaveFile::peak_chunk()`.
+#[derive(Debug,Clone,PartialEq)]
+pub struct PeakChunk {
+  /// Version of the chunk format.
+  pub version:   u32,
+  /// When the peaks were computed, in seconds since January 1, 1970.
+  pub timestamp: u32,
+  /// The peak of each channel, in channel order.
+  pub peaks:     Vec<PeakInfo>
+}
+
+/// The peak of a single channel.  See `PeakChunk`.
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct PeakInfo {
+  /// The peak sample value, on the same `[-1, 1]` scale as the samples
+  /// yielded by `WaveFileIterator`.
+  pub value:    f32,
+  /// The index of the frame the peak occurs at.
+  pub position: u32
+}
+
+impl PeakChunk {
+  pub(crate) fn read<B: ByteOrder>(data: &[u8]) -> Result<PeakChunk, WaveError> {
+    if data.len() < 8 {
+      return Err(WaveError::parse("Peak chunk is too short"));
+    }
+
+    let peaks = data[8..].chunks_exact(8).map(|peak| PeakInfo {
+      value:    B::read_f32(&peak[0..4]),
+      position: B::read_u32(&peak[4..8])
+    }).collect();
+
+    Ok(PeakChunk {
+      version:   B::read_u32(&data[0..4]),
+      timestamp: B::read_u32(&data[4..8]),
+      peaks
+    })
+  }
+}
+
+/// Descriptive text stored in a `LIST INFO` chunk.  Each field which is set
+/// is stored as a sub-chunk with the given id.
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct Metadata {
+  /// Title of the recording (`INAM`).
+  pub title:         Option<String>,
+  /// Artist or creator (`IART`).
+  pub artist:        Option<String>,
+  /// Album or product the recording belongs to (`IPRD`).
+  pub album:         Option<String>,
+  /// Copyright notice (`ICOP`).
+  pub copyright:     Option<String>,
+  /// Creation date, conventionally `YYYY-MM-DD` (`ICRD`).
+  pub creation_date: Option<String>,
+  /// Genre (`IGNR`).
+  pub genre:         Option<String>,
+  /// Free form comment (`ICMT`).
+  pub comment:       Option<String>,
+  /// Software used to create the file (`ISFT`).
+  pub software:      Option<String>
+}
+
+impl Metadata {
+  /// The sub-chunk id and value of every field which is set, in the order
+  /// they are written.
+  pub fn entries(&self) -> Vec<(&'static [u8; 4], &str)> {
+    let fields = [
+      (b"INAM", &self.title),
+      (b"IART", &self.artist),
+      (b"IPRD", &self.album),
+      (b"ICOP", &self.copyright),
+      (b"ICRD", &self.creation_date),
+      (b"IGNR", &self.genre),
+      (b"ICMT", &self.comment),
+      (b"ISFT", &self.software)
+    ];
+
+    fields.iter()
+      .filter_map(|&(id, value)| value.as_ref().map(|value| (id, value.as_str())))
+      .collect()
+  }
+}
+
+/// A chapter mark, made up of a cue point and the label and note attached
+/// to it in the `LIST adtl` chunk.  See `WaveFile::chapters()`.
+#[derive(Debug,Clone,PartialEq)]
+pub struct Chapter {
+  /// The id of the cue point, which associates it with its label and note.
+  pub id:    u32,
+  /// The index of the frame the chapter starts at.
+  pub frame: u32,
+  /// The chapter's title (`labl`), or an empty string if it has none.
+  pub label: String,
+  /// A longer description of the chapter (`note`), if present.
+  pub note:  Option<String>
+}
+
+impl WaveFile {
+  /// The chapter marks of the file, as written by podcast and audiobook
+  /// tools: each cue point in the `cue ` chunk, along with its label and
+  /// note from the `LIST adtl` chunk.  Chapters are sorted by their start
+  /// frame.  Files without a `cue ` chunk have no chapters.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// for chapter in wav.chapters() {
+  ///   println!("{:?}: {}", wav.elapsed_at(chapter.frame as usize), chapter.label);
+  /// }
+  /// ```
+  pub fn chapters(&self) -> Vec<Chapter> {
+    let cue = match self.chunks_iter().find(|chunk| chunk.id == CUE_) {
+      Some(chunk) => chunk.data,
+      None        => return Vec::new()
+    };
+
+    let adtl = self.chunks_iter()
+      .find(|chunk| chunk.id == LIST && chunk.data.len() >= 4 && LittleEndian::read_u32(&chunk.data[0..4]) == ADTL)
+      .map(|chunk| chunk.data);
+
+    if self.big_endian {
+      read_chapters::<BigEndian>(cue, adtl, true)
+    } else {
+      read_chapters::<LittleEndian>(cue, adtl, false)
+    }
+  }
+}
+
+// a `cue ` chunk holds the number of cue points followed by 24 bytes for
+// each: its id, position, data chunk id, chunk start, block start and
+// sample offset.  Truncated points and text chunks are skipped.
+fn read_chapters<B: ByteOrder>(cue: &[u8], adtl: Option<&[u8]>, big_endian: bool) -> Vec<Chapter> {
+  if cue.len() < 4 {
+    return Vec::new();
+  }
+
+  let count = B::read_u32(&cue[0..4]) as usize;
+
+  let mut chapters = cue[4..].chunks_exact(24).take(count).map(|point| Chapter {
+    id:    B::read_u32(&point[0..4]),
+    frame: B::read_u32(&point[20..24]),
+    label: String::new(),
+    note:  None
+  }).collect::<Vec<_>>();
+
+  for chunk in adtl.map(|data| ChunkIterator::list(data, big_endian)).into_iter().flatten() {
+    if chunk.data.len() < 4 || !(chunk.id == LABL || chunk.id == NOTE) {
+      continue;
+    }
+
+    let id   = B::read_u32(&chunk.data[0..4]);
+    let text = read_text(&chunk.data[4..]);
+
+    if let Some(chapter) = chapters.iter_mut().find(|chapter| chapter.id == id) {
+      if chunk.id == LABL {
+        chapter.label = text;
+      } else {
+        chapter.note = Some(text);
+      }
+    }
+  }
+
+  chapters.sort_by_key(|chapter| chapter.frame);
+  chapters
+}
+
+// reads a null terminated string, replacing any invalid UTF-8.
+fn read_text(data: &[u8]) -> String {
+  let end = data.iter().position(|&byte| byte == 0).unwrap_or(data.len());
+  String::from_utf8_lossy(&data[..end]).into_owned()
+}
+
+#[cfg(all(test, not(feature = "no_std")))]
+fn text_chunk(id: &[u8; 4], cue_id: u32, text: &str) -> Vec<u8> {
+  let size = 4 + text.len() as u32 + 1;
+
+  let mut bytes = id.to_vec();
+  bytes.extend_from_slice(&size.to_le_bytes());
+  bytes.extend_from_slice(&cue_id.to_le_bytes());
+  bytes.extend_from_slice(text.as_bytes());
+  bytes.push(0);
+
+  if size & 1 == 1 {
+    bytes.push(0);
+  }
+
+  bytes
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_chapters() {
+  use super::{build_wave, pcm_fmt};
+
+  let mut cue = 2u32.to_le_bytes().to_vec();
+
+  for &(id, frame) in &[(2u32, 6u32), (1, 0)] {
+    cue.extend_from_slice(&id.to_le_bytes());
+    cue.extend_from_slice(&frame.to_le_bytes());
+    cue.extend_from_slice(b"data");
+    cue.extend_from_slice(&[0; 8]);
+    cue.extend_from_slice(&frame.to_le_bytes());
+  }
+
+  let mut adtl = b"adtl".to_vec();
+  adtl.extend(text_chunk(b"labl", 1, "Intro"));
+  adtl.extend(text_chunk(b"labl", 2, "Interview"));
+  adtl.extend(text_chunk(b"note", 2, "With a guest"));
+  adtl.extend(text_chunk(b"labl", 3, "No such cue"));
+
+  let mut bytes = build_wave(&pcm_fmt(1, 8000, 16), &[0; 16]);
+
+  for &(id, data) in &[(b"cue ", &cue), (b"LIST", &adtl)] {
+    bytes.extend_from_slice(id);
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(data);
+  }
+
+  let riff_size = (bytes.len() - 8) as u32;
+  bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+
+  let file = WaveFile::from_vec(bytes).unwrap();
+
+  assert_eq!(file.chapters(), [
+    Chapter { id: 1, frame: 0, label: "Intro".into(),     note: None },
+    Chapter { id: 2, frame: 6, label: "Interview".into(), note: Some("With a guest".into()) }
+  ]);
+  assert!(WaveFile::open("./fixtures/test-f32le.wav").unwrap().chapters().is_empty());
+}
@@ -0,0 +1,109 @@
+#[cfg(feature = "no_std")]
+use alloc::vec::Vec;
+
+use super::{Frame, WaveError, WaveFile, WaveFileIterator};
+
+/// An iterator which yields the weighted sum of the frames of several
+/// wavefiles.  See `mix_frames()`.
+pub struct MixIterator<'a> {
+  frames:   Vec<WaveFileIterator<'a>>,
+  weights:  Vec<f32>,
+  channels: usize
+}
+
+/// Mixes `files` together, returning an iterator which yields the sum of
+/// the frames at each position in all files, each multiplied by the
+/// corresponding entry in `weights`.  Files which are shorter than the
+/// longest one are padded with silence.
+///
+/// All files must be compatible with each other (see
+/// `WaveInfo::compatible_with()`), and there must be exactly one weight per
+/// file; otherwise `WaveError::Incompatible` is returned.  The mix is not
+/// clipped, so samples may exceed `[-1, 1]` if the weights add up to more
+/// than one.
+///
+/// # Example
+///
+/// ```
+/// use wavefile::{WaveFile,mix_frames};
+///
+/// let files = vec![
+///   WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap(),
+///   WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap()
+/// ];
+///
+/// for frame in mix_frames(&files, &[0.5, 0.5]).unwrap() {
+///   println!("{:?}", frame);
+/// }
+/// ```
+pub fn mix_frames<'a>(files: &'a [WaveFile], weights: &[f32]) -> Result<MixIterator<'a>, WaveError> {
+  if files.len() != weights.len() {
+    let msg = format!("{} weight(s) do not match {} file(s)", weights.len(), files.len());
+    return Err(WaveError::Incompatible(msg));
+  }
+
+  if let Some(first) = files.first() {
+    for file in &files[1..] {
+      if let Some(e) = first.info.compatibility_error(&file.info) {
+        return Err(e);
+      }
+    }
+  }
+
+  Ok(MixIterator {
+    frames:   files.iter().map(|file| file.iter()).collect(),
+    weights:  weights.to_vec(),
+    channels: files.first().map_or(0, |file| file.channels() as usize)
+  })
+}
+
+impl<'a> Iterator for MixIterator<'a> {
+  type Item = Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let mut mixed = vec![0.0; self.channels];
+    let mut any   = false;
+
+    for (frames, &weight) in self.frames.iter_mut().zip(self.weights.iter()) {
+      if let Some(frame) = frames.next() {
+        for (sum, sample) in mixed.iter_mut().zip(frame) {
+          *sum += sample * weight;
+        }
+
+        any = true;
+      }
+    }
+
+    if any {
+      Some(mixed)
+    } else {
+      None
+    }
+  }
+}
+
+#[test]
+fn test_mix_frames() {
+  use super::pcm16_wave;
+
+  let files = vec![
+    pcm16_wave(1, &[16384, 16384, 16384]),
+    pcm16_wave(1, &[-8192, 8192])
+  ];
+
+  let mixed = mix_frames(&files, &[0.5, 1.0]).unwrap().collect::<Vec<_>>();
+  assert_eq!(mixed, [[0.0], [0.5], [0.25]]);
+
+  assert!(mix_frames(&files, &[1.0]).is_err());
+  assert_eq!(mix_frames(&[], &[]).unwrap().count(), 0);
+
+  let other = vec![
+    files[0].clone_into_memory(),
+    pcm16_wave(2, &[0, 0])
+  ];
+
+  match mix_frames(&other, &[1.0, 1.0]) {
+    Err(WaveError::Incompatible(_)) => (),
+    _                               => panic!("expected an incompatible format error")
+  }
+}
@@ -0,0 +1,184 @@
+use core::fmt;
+#[cfg(feature = "no_std")]
+use alloc::vec::Vec;
+#[cfg(feature = "no_std")]
+use alloc::sync::Arc;
+#[cfg(not(feature = "no_std"))]
+use std::sync::Arc;
+#[cfg(not(feature = "no_std"))]
+use std::io::{self,Read};
+#[cfg(not(feature = "no_std"))]
+use std::fs::{self, File};
+#[cfg(not(feature = "no_std"))]
+use std::path::Path;
+#[cfg(not(feature = "no_std"))]
+use memmap::Mmap;
+
+use super::{Buffer, WaveError, WaveFile};
+
+/// Options controlling how a wavefile is parsed, in the style of
+/// `std::fs::OpenOptions`.
+///
+/// # Example
+///
+/// ```
+/// use wavefile::OpenOptions;
+///
+/// let wav = OpenOptions::new()
+///   .lenient(true)
+///   .open("./fixtures/test-s24le.wav")
+///   .unwrap();
+/// ```
+#[derive(Clone,Default)]
+pub struct OpenOptions {
+  pub(crate) lenient: bool,
+  on_progress:        Option<Arc<dyn Fn(u64, u64) + Send + Sync>>
+}
+
+impl OpenOptions {
+  /// Creates a new set of options, with strict parsing enabled.
+  pub fn new() -> OpenOptions {
+    OpenOptions::default()
+  }
+
+  /// Sets whether header inconsistencies that do not prevent decoding, such
+  /// as a `byte_rate` which disagrees with `sample_rate` and `block_align`,
+  /// should be tolerated.  By default they are reported as errors.
+  ///
+  /// Lenient parsing also skips stray null bytes between chunks, which some
+  /// DAWs write without accounting for them in the chunk size.
+  pub fn lenient(&mut self, lenient: bool) -> &mut OpenOptions {
+    self.lenient = lenient;
+    self
+  }
+
+  /// Sets a callback which is invoked after each chunk of the file has been
+  /// read while opening it, with the number of bytes processed so far and
+  /// the total size of the file.  Useful for displaying progress when
+  /// opening large files with many chunks before the audio data.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::OpenOptions;
+  ///
+  /// let wav = OpenOptions::new()
+  ///   .on_progress(|processed, total| println!("{} / {} bytes", processed, total))
+  ///   .open("./fixtures/test-s24le.wav")
+  ///   .unwrap();
+  /// ```
+  pub fn on_progress<F: Fn(u64, u64) + Send + Sync + 'static>(&mut self, callback: F) -> &mut OpenOptions {
+    self.on_progress = Some(Arc::new(callback));
+    self
+  }
+
+  pub(crate) fn report_progress(&self, processed: u64, total: u64) {
+    if let Some(ref callback) = self.on_progress {
+      callback(processed, total);
+    }
+  }
+
+  /// Opens the wavefile at `path` with these options, or reads it from
+  /// standard input if `path` is `-`.
+  /// Any error returned includes `path` for context.
+  ///
+  /// The file is memory mapped, unless the `safe` feature is enabled, in
+  /// which case this is the same as `open_safe()`.
+  #[cfg(not(feature = "no_std"))]
+  pub fn open<P: AsRef<Path>>(&self, path: P) -> Result<WaveFile, WaveError> {
+    if cfg!(feature = "safe") {
+      self.open_safe(path)
+    } else {
+      self.open_mmap(path)
+    }
+  }
+
+  /// Like `open()`, but always memory maps the file, even if the `safe`
+  /// feature is enabled.  See `WaveFile::open_mmap()`.
+  #[cfg(not(feature = "no_std"))]
+  pub fn open_mmap<P: AsRef<Path>>(&self, path: P) -> Result<WaveFile, WaveError> {
+    self.open_with(path, OpenOptions::open_mapped)
+  }
+
+  /// Like `open()`, but always reads the whole file into memory instead of
+  /// mapping it.  See `WaveFile::open_safe()`.
+  #[cfg(not(feature = "no_std"))]
+  pub fn open_safe<P: AsRef<Path>>(&self, path: P) -> Result<WaveFile, WaveError> {
+    self.open_with(path, OpenOptions::open_read)
+  }
+
+  /// Reads a wavefile from standard input with these options.
+  /// See `WaveFile::open_stdin()`.
+  #[cfg(not(feature = "no_std"))]
+  pub fn open_stdin(&self) -> Result<WaveFile, WaveError> {
+    let mut bytes = Vec::new();
+    io::stdin().read_to_end(&mut bytes)?;
+
+    self.open_vec(bytes)
+  }
+
+  /// Parses a wavefile held in memory with these options.
+  /// See `WaveFile::from_vec()`.
+  pub fn open_vec(&self, bytes: Vec<u8>) -> Result<WaveFile, WaveError> {
+    WaveFile::from_buffer(Buffer::Owned(bytes), self)
+  }
+
+  // handles `-` and attaches `path` to the file and to any error, around
+  // one of the ways of loading a file below.
+  #[cfg(not(feature = "no_std"))]
+  fn open_with<P: AsRef<Path>>(&self, path: P, load: fn(&OpenOptions, &Path) -> Result<WaveFile, WaveError>) -> Result<WaveFile, WaveError> {
+    let result = if path.as_ref() == Path::new("-") {
+      self.open_stdin()
+    } else {
+      load(self, path.as_ref()).map(|mut file| {
+        file.path = Some(path.as_ref().to_path_buf());
+        file
+      })
+    };
+
+    result.map_err(|e| e.with_path(path))
+  }
+
+  #[cfg(not(feature = "no_std"))]
+  fn open_read(&self, path: &Path) -> Result<WaveFile, WaveError> {
+    self.open_vec(fs::read(path)?)
+  }
+
+  #[cfg(not(feature = "no_std"))]
+  fn open_mapped(&self, path: &Path) -> Result<WaveFile, WaveError> {
+    let file = File::open(path)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+
+    WaveFile::from_buffer(Buffer::Mapped(mmap), self)
+  }
+}
+
+impl fmt::Debug for OpenOptions {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("OpenOptions")
+      .field("lenient", &self.lenient)
+      .field("on_progress", &self.on_progress.is_some())
+      .finish()
+  }
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_on_progress() {
+  use std::sync::Mutex;
+
+  let calls  = Arc::new(Mutex::new(Vec::new()));
+  let record = calls.clone();
+
+  let file = OpenOptions::new()
+    .on_progress(move |processed, total| record.lock().unwrap().push((processed, total)))
+    .open("./fixtures/test-f32le.wav")
+    .unwrap();
+
+  let calls = calls.lock().unwrap();
+  let total = file.header_bytes().len() as u64 + file.len() as u64 * 8;
+
+  assert_eq!(calls.len(), file.chunks_iter().count());
+  assert_eq!(calls.last(), Some(&(total, total)));
+  assert!(calls.windows(2).all(|pair| pair[0].0 < pair[1].0));
+}
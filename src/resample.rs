@@ -0,0 +1,314 @@
+#[cfg(not(feature = "no_std"))]
+use core::f64::consts::PI;
+
+use super::{Frame, WaveFile, WaveFileIterator};
+#[cfg(not(feature = "no_std"))]
+use super::WaveError;
+
+/// The number of zero crossings of the sinc kernel used by
+/// `WaveFile::resample_to()` on each side of an output sample.
+pub const DEFAULT_ZERO_CROSSINGS: usize = 16;
+
+/// The interpolation method used by a `ResampledIterator`.
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub enum ResampleQuality {
+  /// Linear interpolation between the two nearest source frames.
+  Linear
+}
+
+/// An iterator which yields the frames of a wavefile converted to a different
+/// sample rate.  See `WaveFile::iter_resampled()`.
+pub struct ResampledIterator<'a> {
+  frames:      WaveFileIterator<'a>,
+  quality:     ResampleQuality,
+  source_rate: u64,
+  target_rate: u64,
+  total:       u64,
+  index:       u64,
+  current:     Option<Frame>,
+  following:   Option<Frame>,
+  position:    u64
+}
+
+impl WaveFile {
+  /// Returns an iterator which yields the frames of this file converted to
+  /// `target_rate` samples per second, using linear interpolation.
+  ///
+  /// If `target_rate` is the same as `sample_rate()`, frames are passed
+  /// through unchanged.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// for frame in wav.iter_resampled(44100).take(10) {
+  ///   println!("{:?}", frame);
+  /// }
+  /// ```
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn iter_resampled(&self, target_rate: u32) -> ResampledIterator<'_> {
+    self.iter_resampled_with(target_rate, ResampleQuality::Linear)
+  }
+
+  /// Like `iter_resampled()`, but with an explicit interpolation method.
+  #[must_use = "iterators do nothing unless driven"]
+  pub fn iter_resampled_with(&self, target_rate: u32, quality: ResampleQuality) -> ResampledIterator<'_> {
+    let source_rate = self.sample_rate() as u64;
+    let target_rate = target_rate as u64;
+    let total = if source_rate == 0 {
+      0
+    } else {
+      (self.len() as u64 * target_rate).div_ceil(source_rate)
+    };
+
+    ResampledIterator {
+      frames:    self.iter(),
+      quality,
+      source_rate,
+      target_rate,
+      total,
+      index:     0,
+      current:   None,
+      following: None,
+      position:  0
+    }
+  }
+
+  /// Converts the entire file to `target_rate` samples per second using
+  /// windowed sinc interpolation, which is considerably more accurate than
+  /// the linear interpolation of `iter_resampled()`.  When downsampling,
+  /// frequencies above the new Nyquist frequency are filtered out.
+  ///
+  /// Uses a Lanczos kernel with `DEFAULT_ZERO_CROSSINGS` zero crossings;
+  /// see `resample_to_with()`.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav    = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+  /// let frames = wav.resample_to(44100).unwrap();
+  ///
+  /// assert_eq!(frames.len(), wav.iter_resampled(44100).count());
+  /// ```
+  #[cfg(not(feature = "no_std"))]
+  pub fn resample_to(&self, target_rate: u32) -> Result<Vec<Frame>, WaveError> {
+    self.resample_to_with(target_rate, DEFAULT_ZERO_CROSSINGS)
+  }
+
+  /// Like `resample_to()`, but with an explicit number of zero crossings on
+  /// each side of the kernel.  More zero crossings give a steeper filter at
+  /// the cost of more computation per output frame.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `zero_crossings` is zero.
+  #[cfg(not(feature = "no_std"))]
+  pub fn resample_to_with(&self, target_rate: u32, zero_crossings: usize) -> Result<Vec<Frame>, WaveError> {
+    assert!(zero_crossings != 0, "resample_to_with requires at least one zero crossing");
+
+    if self.sample_rate() == 0 || target_rate == 0 {
+      let msg = format!("Cannot resample from {} Hz to {} Hz", self.sample_rate(), target_rate);
+      return Err(WaveError::Unsupported(msg));
+    }
+
+    let source = self.iter().collect::<Vec<_>>();
+
+    if self.sample_rate() == target_rate {
+      return Ok(source);
+    }
+
+    let source_rate = self.sample_rate() as u64;
+    let target_rate = target_rate as u64;
+    let total       = (source.len() as u64 * target_rate).div_ceil(source_rate);
+
+    // when downsampling, the kernel is stretched so that it cuts off at the
+    // target's Nyquist frequency rather than the source's.
+    let scale  = (target_rate as f64 / source_rate as f64).min(1.0);
+    let radius = zero_crossings as f64 / scale;
+    let step   = source_rate as f64 / target_rate as f64;
+
+    let channels  = self.channels() as usize;
+    let mut frames = Vec::with_capacity(total as usize);
+
+    for index in 0..total {
+      let t     = index as f64 * step;
+      let first = (t - radius).floor().max(0.0) as usize;
+      let last  = ((t + radius).ceil() as usize).min(source.len() - 1);
+
+      let mut sums   = vec![0.0f64; channels];
+      let mut weight = 0.0;
+
+      for (j, frame) in source.iter().enumerate().take(last + 1).skip(first) {
+        let w = scale * lanczos((t - j as f64) * scale, zero_crossings as f64);
+
+        for (sum, &sample) in sums.iter_mut().zip(frame.iter()) {
+          *sum += sample as f64 * w;
+        }
+
+        weight += w;
+      }
+
+      // normalizing by the total weight keeps the gain at exactly one, also
+      // near the start and end of the file where the kernel is cut off.
+      let norm = if weight == 0.0 { 1.0 } else { weight };
+      frames.push(sums.iter().map(|sum| (sum / norm) as f32).collect());
+    }
+
+    Ok(frames)
+  }
+}
+
+// the normalized sinc function, windowed by a wider sinc which reaches
+// zero after `a` zero crossings.
+#[cfg(not(feature = "no_std"))]
+fn lanczos(x: f64, a: f64) -> f64 {
+  if x == 0.0 {
+    return 1.0;
+  }
+
+  if x.abs() >= a {
+    return 0.0;
+  }
+
+  let px = PI * x;
+  a * px.sin() * (px / a).sin() / (px * px)
+}
+
+impl<'a> ResampledIterator<'a> {
+  /// The interpolation method used by this iterator.
+  pub fn quality(&self) -> ResampleQuality {
+    self.quality
+  }
+
+  // advances the source frames so that `current` holds the frame at
+  // `position` and `following` holds the one after it (if any).
+  fn seek_source(&mut self, position: u64) {
+    if self.current.is_none() {
+      self.current   = self.frames.next();
+      self.following = self.frames.next();
+      self.position  = 0;
+    }
+
+    while self.position < position && self.following.is_some() {
+      self.current   = self.following.take();
+      self.following = self.frames.next();
+      self.position += 1;
+    }
+  }
+
+  fn interpolate_linear(&self, fraction: f32) -> Option<Frame> {
+    let current = self.current.as_ref()?;
+
+    match self.following {
+      Some(ref following) => Some(
+        current.iter()
+          .zip(following.iter())
+          .map(|(a, b)| a + (b - a) * fraction)
+          .collect()
+      ),
+      None => Some(current.clone())
+    }
+  }
+}
+
+impl<'a> Iterator for ResampledIterator<'a> {
+  type Item = Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.index >= self.total {
+      return None;
+    }
+
+    if self.source_rate == self.target_rate {
+      self.index += 1;
+      return self.frames.next();
+    }
+
+    // the output frame `index` lies at `index * source / target` in the
+    // source; split that into a whole frame position and a fraction.
+    let scaled   = self.index * self.source_rate;
+    let position = scaled / self.target_rate;
+    let fraction = (scaled % self.target_rate) as f32 / self.target_rate as f32;
+
+    self.seek_source(position);
+    self.index += 1;
+
+    match self.quality {
+      ResampleQuality::Linear => self.interpolate_linear(fraction)
+    }
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let remaining = (self.total - self.index) as usize;
+    (remaining, Some(remaining))
+  }
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_resample_linear() {
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+
+  let iter = file.iter_resampled(16000);
+  assert_eq!(iter.size_hint(), (64, Some(64)));
+
+  let frames = iter.collect::<Vec<_>>();
+  assert_eq!(frames.len(), 64);
+  assert_eq!(frames[0], [0.0, 0.0]);
+  assert_eq!(frames[1], [0.015625, -0.015625]);
+  assert_eq!(frames[2], [0.03125, -0.03125]);
+  assert_eq!(frames[63], frames[62]);
+
+  assert_eq!(file.iter_resampled(4000).count(), 16);
+}
+
+#[cfg(all(test, not(feature = "no_std")))]
+use super::sine_wave;
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_resample_sinc() {
+  let file   = sine_wave(8000, 1000.0, 800);
+  let frames = file.resample_to(16000).unwrap();
+
+  assert_eq!(frames.len(), 1600);
+
+  // away from the edges, the output should match the ideal sine closely.
+  for (i, frame) in frames.iter().enumerate().skip(200).take(1200) {
+    let expected = (2.0 * PI * 1000.0 * i as f64 / 16000.0).sin() * 0.5;
+    assert!((frame[0] as f64 - expected).abs() < 1e-3, "frame {}: {}", i, frame[0]);
+  }
+
+  // a 3 kHz tone is above the Nyquist frequency at 4 kHz and filtered out.
+  let file   = sine_wave(8000, 3000.0, 800);
+  let frames = file.resample_to_with(4000, 32).unwrap();
+  let peak   = frames[100..300].iter().map(|frame| frame[0].abs()).fold(0.0, f32::max);
+
+  assert_eq!(frames.len(), 400);
+  assert!(peak < 0.01, "peak {}", peak);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_resample_sinc_edge_cases() {
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+
+  assert_eq!(file.resample_to(8000).unwrap(), file.iter().collect::<Vec<_>>());
+  assert!(file.resample_to(0).is_err());
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn test_resample_same_rate() {
+  let file = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+
+  let resampled = file.iter_resampled(8000).collect::<Vec<_>>();
+  let original  = file.iter().collect::<Vec<_>>();
+
+  assert_eq!(resampled, original);
+}
@@ -1,24 +1,61 @@
+#[cfg(feature = "no_std")]
+use alloc::vec::Vec;
 
 const SPEAKER_FRONT_LEFT            : isize =  1;
-const SPEAKER_FRONT_RIGHT           : isize = (1 << 1);
-const SPEAKER_FRONT_CENTER          : isize = (1 << 2);
-const SPEAKER_LOW_FREQUENCY         : isize = (1 << 3);
-const SPEAKER_BACK_LEFT             : isize = (1 << 4);
-const SPEAKER_BACK_RIGHT            : isize = (1 << 5);
-const SPEAKER_FRONT_LEFT_OF_CENTER  : isize = (1 << 6);
-const SPEAKER_FRONT_RIGHT_OF_CENTER : isize = (1 << 7);
-const SPEAKER_BACK_CENTER           : isize = (1 << 8);
-const SPEAKER_SIDE_LEFT             : isize = (1 << 9);
-const SPEAKER_SIDE_RIGHT            : isize = (1 << 10);
-const SPEAKER_TOP_CENTER            : isize = (1 << 11);
-const SPEAKER_TOP_FRONT_LEFT        : isize = (1 << 12);
-const SPEAKER_TOP_FRONT_CENTER      : isize = (1 << 13);
-const SPEAKER_TOP_FRONT_RIGHT       : isize = (1 << 14);
-const SPEAKER_TOP_BACK_LEFT         : isize = (1 << 15);
-const SPEAKER_TOP_BACK_CENTER       : isize = (1 << 16);
-const SPEAKER_TOP_BACK_RIGHT        : isize = (1 << 17);
+const SPEAKER_FRONT_RIGHT           : isize = 1 << 1;
+const SPEAKER_FRONT_CENTER          : isize = 1 << 2;
+const SPEAKER_LOW_FREQUENCY         : isize = 1 << 3;
+const SPEAKER_BACK_LEFT             : isize = 1 << 4;
+const SPEAKER_BACK_RIGHT            : isize = 1 << 5;
+const SPEAKER_FRONT_LEFT_OF_CENTER  : isize = 1 << 6;
+const SPEAKER_FRONT_RIGHT_OF_CENTER : isize = 1 << 7;
+const SPEAKER_BACK_CENTER           : isize = 1 << 8;
+const SPEAKER_SIDE_LEFT             : isize = 1 << 9;
+const SPEAKER_SIDE_RIGHT            : isize = 1 << 10;
+const SPEAKER_TOP_CENTER            : isize = 1 << 11;
+const SPEAKER_TOP_FRONT_LEFT        : isize = 1 << 12;
+const SPEAKER_TOP_FRONT_CENTER      : isize = 1 << 13;
+const SPEAKER_TOP_FRONT_RIGHT       : isize = 1 << 14;
+const SPEAKER_TOP_BACK_LEFT         : isize = 1 << 15;
+const SPEAKER_TOP_BACK_CENTER       : isize = 1 << 16;
+const SPEAKER_TOP_BACK_RIGHT        : isize = 1 << 17;
+
+const FRONT_PAIR : isize = SPEAKER_FRONT_LEFT | SPEAKER_FRONT_RIGHT;
+const BACK_PAIR  : isize = SPEAKER_BACK_LEFT | SPEAKER_BACK_RIGHT;
+const SIDE_PAIR  : isize = SPEAKER_SIDE_LEFT | SPEAKER_SIDE_RIGHT;
+const WIDE_PAIR  : isize = SPEAKER_FRONT_LEFT_OF_CENTER | SPEAKER_FRONT_RIGHT_OF_CENTER;
+const TOP_FRONT  : isize = SPEAKER_TOP_FRONT_LEFT | SPEAKER_TOP_FRONT_RIGHT;
+const TOP_BACK   : isize = SPEAKER_TOP_BACK_LEFT | SPEAKER_TOP_BACK_RIGHT;
+const SURROUND   : isize = FRONT_PAIR | SPEAKER_FRONT_CENTER;
+const LFE        : isize = SPEAKER_LOW_FREQUENCY;
+
+// the standard channel masks and their canonical names.  Layouts such as
+// 5.1 are commonly written with either back or side surround speakers.
+const LAYOUTS : [(isize, &str); 20] = [
+  (SPEAKER_FRONT_CENTER,                                        "1.0"),
+  (FRONT_PAIR,                                                  "2.0"),
+  (FRONT_PAIR | LFE,                                            "2.1"),
+  (SURROUND,                                                    "3.0"),
+  (SURROUND | LFE,                                              "3.1"),
+  (FRONT_PAIR | BACK_PAIR,                                      "4.0"),
+  (SURROUND | SPEAKER_BACK_CENTER,                              "4.0"),
+  (FRONT_PAIR | LFE | BACK_PAIR,                                "4.1"),
+  (SURROUND | BACK_PAIR,                                        "5.0"),
+  (SURROUND | SIDE_PAIR,                                        "5.0"),
+  (SURROUND | LFE | BACK_PAIR,                                  "5.1"),
+  (SURROUND | LFE | SIDE_PAIR,                                  "5.1"),
+  (SURROUND | LFE | BACK_PAIR | SPEAKER_BACK_CENTER,            "6.1"),
+  (SURROUND | LFE | SIDE_PAIR | SPEAKER_BACK_CENTER,            "6.1"),
+  (SURROUND | LFE | BACK_PAIR | SIDE_PAIR,                      "7.1"),
+  (SURROUND | LFE | BACK_PAIR | WIDE_PAIR,                      "7.1"),
+  (SURROUND | LFE | SIDE_PAIR | TOP_FRONT,                      "5.1.2"),
+  (SURROUND | LFE | SIDE_PAIR | TOP_FRONT | TOP_BACK,           "5.1.4"),
+  (SURROUND | LFE | BACK_PAIR | SIDE_PAIR | TOP_FRONT,          "7.1.2"),
+  (SURROUND | LFE | BACK_PAIR | SIDE_PAIR | TOP_FRONT | TOP_BACK, "7.1.4")
+];
 
 #[derive(Debug,Copy,Clone,PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SpeakerPosition {
   FrontLeft          = SPEAKER_FRONT_LEFT,
   FrontRight         = SPEAKER_FRONT_RIGHT,
@@ -40,6 +77,16 @@ pub enum SpeakerPosition {
   TopBackRight       = SPEAKER_TOP_BACK_RIGHT
 }
 
+/// Describes a single audio channel.  See `WaveFile::channels_info()`.
+#[derive(Debug,Copy,Clone,PartialEq)]
+pub struct ChannelInfo {
+  /// The index of the channel within each frame.
+  pub index:   usize,
+  /// The speaker this channel is intended for, if the file has a channel
+  /// mask assigning one to it.
+  pub speaker: Option<SpeakerPosition>
+}
+
 impl SpeakerPosition {
   pub fn decode(bits: isize) -> Vec<SpeakerPosition> {
     let mut speakers = Vec::with_capacity(8);
@@ -73,4 +120,34 @@ impl SpeakerPosition {
     }
     speakers
   }
+
+  /// The canonical name of the speaker layout described by a channel mask,
+  /// such as `"5.1"` or `"7.1.4"`, or `None` if `mask` is not one of the
+  /// standard layouts.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::SpeakerPosition;
+  ///
+  /// assert_eq!(SpeakerPosition::layout_name(0x3), Some("2.0"));
+  /// assert_eq!(SpeakerPosition::layout_name(0x60f), Some("5.1"));
+  /// assert_eq!(SpeakerPosition::layout_name(0x5), None);
+  /// ```
+  pub fn layout_name(mask: u32) -> Option<&'static str> {
+    LAYOUTS.iter()
+      .find(|&&(bits, _)| bits == mask as isize)
+      .map(|&(_, name)| name)
+  }
+}
+
+#[test]
+fn test_layout_name() {
+  assert_eq!(SpeakerPosition::layout_name(0x4), Some("1.0"));
+  assert_eq!(SpeakerPosition::layout_name(0x3f), Some("5.1"));
+  assert_eq!(SpeakerPosition::layout_name(0x63f), Some("7.1"));
+  assert_eq!(SpeakerPosition::layout_name(0xff), Some("7.1"));
+  assert_eq!(SpeakerPosition::layout_name(0x2d63f), Some("7.1.4"));
+  assert_eq!(SpeakerPosition::layout_name(0), None);
+  assert_eq!(SpeakerPosition::layout_name(0x3 | 0x80000000), None);
 }
@@ -0,0 +1,749 @@
+use std::cmp;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use byteorder::{ByteOrder, BigEndian, LittleEndian};
+
+use super::{Format, Frame, Metadata, WaveError, WaveFile, WaveInfo};
+use super::{RF64, RIFF, RIFX, WAVE, W64_RIFF};
+
+/// Writes audio frames to a new wavefile.
+///
+/// The header is written when the first frame is written, and the chunk
+/// sizes are filled in by `finalize()`, which must be called once all frames
+/// have been written.  Samples are given in the same `[-1, 1]` range the
+/// `WaveFileIterator` yields, and are converted to the format described by
+/// the `WaveInfo` passed on creation.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use wavefile::{WaveFile,WaveInfo,WaveWriter};
+///
+/// let mut writer = WaveWriter::new(Cursor::new(Vec::new()), WaveInfo::default()).unwrap();
+///
+/// for _ in 0..100 {
+///   writer.write_frame(&[0.5, -0.5]).unwrap();
+/// }
+///
+/// let bytes = writer.finalize().unwrap().into_inner();
+/// let wav   = WaveFile::from_vec(bytes).unwrap();
+///
+/// assert_eq!(wav.len(), 100);
+/// ```
+pub struct WaveWriter<W: Write + Seek> {
+  writer:        W,
+  info:          WaveInfo,
+  metadata:      Option<Metadata>,
+  // position of the data chunk's size field, once the header is written.
+  data_size_pos: Option<u64>,
+  data_size:     u64,
+  buffer:        Vec<u8>
+}
+
+impl WaveWriter<BufWriter<File>> {
+  /// Creates a new wavefile at `path`, truncating any existing file.
+  /// Any error returned includes `path` for context.
+  pub fn create<P: AsRef<Path>>(path: P, info: WaveInfo) -> Result<WaveWriter<BufWriter<File>>, WaveError> {
+    let create = |path: &Path| -> Result<WaveWriter<BufWriter<File>>, WaveError> {
+      WaveWriter::new(BufWriter::new(File::create(path)?), info)
+    };
+
+    create(path.as_ref()).map_err(|e| e.with_path(path))
+  }
+}
+
+impl<W: Write + Seek> WaveWriter<W> {
+  /// Creates a writer which writes a wavefile described by `info` to
+  /// `writer`.  `block_align` and `byte_rate` are derived from the other
+  /// fields, and `total_frames` is ignored.
+  ///
+  /// Returns `WaveError::Unsupported` unless `info` describes PCM data with
+  /// 8, 16, 24 or 32 bits per sample, or IEEE Float data with 32 or 64 bits,
+  /// and `block_align` and `byte_rate` fit in their header fields.
+  pub fn new(writer: W, info: WaveInfo) -> Result<WaveWriter<W>, WaveError> {
+    let mut info = validate(&info)?;
+    info.total_frames = 0;
+
+    Ok(WaveWriter {
+      writer,
+      info,
+      metadata:      None,
+      data_size_pos: None,
+      data_size:     0,
+      buffer:        Vec::with_capacity(info.block_align as usize)
+    })
+  }
+
+  /// Sets the text metadata written to a `LIST INFO` chunk.
+  ///
+  /// The chunk is written before the `data` chunk, so this should be called
+  /// before the first frame is written; otherwise the chunk is written after
+  /// the audio data instead, which is valid but not understood by all tools.
+  pub fn set_metadata(&mut self, metadata: &Metadata) -> &mut WaveWriter<W> {
+    self.metadata = Some(metadata.clone());
+    self
+  }
+
+  /// The format of the file being written.  `total_frames` holds the number
+  /// of frames written so far.
+  pub fn info(&self) -> WaveInfo {
+    self.info
+  }
+
+  /// Writes a single frame, which must contain exactly one sample per
+  /// channel.  Samples outside of `[-1, 1]` are clipped for PCM data.
+  pub fn write_frame(&mut self, frame: &[f32]) -> Result<(), WaveError> {
+    if frame.len() != self.info.channels as usize {
+      let msg = format!("{} sample(s) do not match {} channel(s)", frame.len(), self.info.channels);
+      return Err(WaveError::Incompatible(msg));
+    }
+
+    if self.data_size_pos.is_none() {
+      self.write_header()?;
+    }
+
+    self.buffer.clear();
+
+    for &sample in frame {
+      self.encode_sample(sample);
+    }
+
+    self.writer.write_all(&self.buffer)?;
+    self.data_size += self.buffer.len() as u64;
+    self.info.total_frames += 1;
+
+    Ok(())
+  }
+
+  /// Completes the file by filling in the chunk sizes, and returns the
+  /// underlying writer.
+  pub fn finalize(mut self) -> Result<W, WaveError> {
+    if self.data_size_pos.is_none() {
+      self.write_header()?;
+    }
+
+    let data_size_pos = self.data_size_pos.unwrap_or(0);
+
+    if self.data_size > u32::MAX as u64 {
+      let msg = format!("{} bytes of audio data do not fit in a RIFF file", self.data_size);
+      return Err(WaveError::Unsupported(msg));
+    }
+
+    // chunks are padded to an even number of bytes.
+    if self.data_size & 1 == 1 {
+      self.writer.write_all(&[0])?;
+    }
+
+    if let Some(metadata) = self.metadata.take() {
+      self.write_list_info(&metadata)?;
+    }
+
+    let end = self.writer.stream_position()?;
+
+    self.writer.seek(SeekFrom::Start(4))?;
+    self.writer.write_all(&((end - 8) as u32).to_le_bytes())?;
+    self.writer.seek(SeekFrom::Start(data_size_pos))?;
+    self.writer.write_all(&(self.data_size as u32).to_le_bytes())?;
+    self.writer.seek(SeekFrom::Start(end))?;
+    self.writer.flush()?;
+
+    Ok(self.writer)
+  }
+
+  // writes the RIFF header, the fmt chunk, any metadata and the data chunk
+  // header, leaving the sizes to be filled in by `finalize()`.
+  fn write_header(&mut self) -> Result<(), WaveError> {
+    self.writer.write_all(b"RIFF\0\0\0\0WAVE")?;
+    self.writer.write_all(&self.info.to_fmt_chunk_bytes())?;
+
+    if let Some(metadata) = self.metadata.take() {
+      self.write_list_info(&metadata)?;
+    }
+
+    self.writer.write_all(b"data")?;
+    self.data_size_pos = Some(self.writer.stream_position()?);
+    self.writer.write_all(&[0; 4])?;
+
+    Ok(())
+  }
+
+  fn write_list_info(&mut self, metadata: &Metadata) -> Result<(), WaveError> {
+    self.writer.write_all(b"LIST")?;
+    let size_pos = self.writer.stream_position()?;
+    self.writer.write_all(b"\0\0\0\0INFO")?;
+
+    for (id, value) in metadata.entries() {
+      let size = value.len() as u32 + 1;
+
+      self.writer.write_all(id)?;
+      self.writer.write_all(&size.to_le_bytes())?;
+      self.writer.write_all(value.as_bytes())?;
+      self.writer.write_all(&[0])?;
+
+      if size & 1 == 1 {
+        self.writer.write_all(&[0])?;
+      }
+    }
+
+    let end = self.writer.stream_position()?;
+
+    self.writer.seek(SeekFrom::Start(size_pos))?;
+    self.writer.write_all(&((end - size_pos - 4) as u32).to_le_bytes())?;
+    self.writer.seek(SeekFrom::Start(end))?;
+
+    Ok(())
+  }
+
+  fn encode_sample(&mut self, sample: f32) {
+    let sample = sample as f64;
+
+    match (self.info.data_format(), self.info.bits_per_sample) {
+      (Format::PCM, 8) => {
+        let value = (sample * 128.0).round().clamp(-128.0, 127.0) + 128.0;
+        self.buffer.push(value as u8);
+      },
+      (Format::PCM, 16) => {
+        let value = (sample * 32768.0).round().clamp(-32768.0, 32767.0) as i16;
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+      },
+      (Format::PCM, 24) => {
+        let value = (sample * 8388608.0).round().clamp(-8388608.0, 8388607.0) as i32;
+        self.buffer.extend_from_slice(&value.to_le_bytes()[..3]);
+      },
+      (Format::PCM, _) => {
+        let value = (sample * 2147483648.0).round().clamp(-2147483648.0, 2147483647.0) as i32;
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+      },
+      (_, 32) => self.buffer.extend_from_slice(&(sample as f32).to_le_bytes()),
+      (_, _)  => self.buffer.extend_from_slice(&sample.to_le_bytes())
+    }
+  }
+}
+
+impl WaveFile {
+  /// Writes the audio of this file to a new wavefile at `dest`, converted
+  /// to `format` with `bps` bits per sample; e.g. to turn a 24-bit
+  /// recording into a 16-bit file.  The channel layout of extensible files
+  /// is preserved.
+  ///
+  /// The target format is validated before `dest` is created; see
+  /// `WaveWriter::new()` for the supported combinations.  Any error returned
+  /// includes `dest` for context.
+  ///
+  /// # Example
+  ///
+  /// ```no_run
+  /// use wavefile::{WaveFile,Format};
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// wav.write_to_path("/tmp/test-s16le.wav", Format::PCM, 16).unwrap();
+  /// ```
+  pub fn write_to_path<P: AsRef<Path>>(&self, dest: P, format: Format, bps: u16) -> Result<(), WaveError> {
+    let mut info = WaveInfo {
+      audio_format:    format,
+      bits_per_sample: bps,
+      valid_bps:       None,
+      channel_mask:    None,
+      subformat:       None,
+      ..self.info()
+    };
+
+    if let Some(mask) = self.info().channel_mask {
+      info.audio_format = Format::Extensible;
+      info.channel_mask = Some(mask);
+      info.subformat    = Some(format);
+    }
+
+    write_frames_to(dest, info, self.iter())
+  }
+
+  /// Writes channel `ch` of this file to a new mono wavefile at `dest`, in
+  /// the same format as this file; e.g. to split a multitrack podcast
+  /// recording into one file per speaker.
+  ///
+  /// Returns `WaveError::Incompatible` if the file has no channel `ch`.  Any
+  /// error writing the new file includes `dest` for context.
+  ///
+  /// # Example
+  ///
+  /// ```no_run
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  ///
+  /// wav.export_channel_as_mono_wav(0, "/tmp/left.wav").unwrap();
+  /// wav.export_channel_as_mono_wav(1, "/tmp/right.wav").unwrap();
+  /// ```
+  pub fn export_channel_as_mono_wav<P: AsRef<Path>>(&self, ch: usize, dest: P) -> Result<(), WaveError> {
+    self.export_channels(&[ch], dest)
+  }
+
+  /// Writes channels `left_ch` and `right_ch` of this file to a new stereo
+  /// wavefile at `dest`, in the same format as this file; e.g. to extract
+  /// the surround pair of a 5.1 mix.
+  ///
+  /// Returns `WaveError::Incompatible` if the file has no channel `left_ch`
+  /// or `right_ch`.  Any error writing the new file includes `dest` for
+  /// context.
+  ///
+  /// # Example
+  ///
+  /// ```no_run
+  /// use wavefile::WaveFile;
+  ///
+  /// let wav = WaveFile::open("/home/john/surround.wav").unwrap();
+  ///
+  /// wav.export_stereo_pair(4, 5, "/tmp/surround-pair.wav").unwrap();
+  /// ```
+  pub fn export_stereo_pair<P: AsRef<Path>>(&self, left_ch: usize, right_ch: usize, dest: P) -> Result<(), WaveError> {
+    self.export_channels(&[left_ch, right_ch], dest)
+  }
+
+  fn export_channels<P: AsRef<Path>>(&self, channels: &[usize], dest: P) -> Result<(), WaveError> {
+    if let Some(&ch) = channels.iter().find(|&&ch| ch >= self.channels() as usize) {
+      let msg = format!("Channel {} does not exist in a file with {} channel(s)", ch, self.channels());
+      return Err(self.in_file(WaveError::Incompatible(msg)));
+    }
+
+    let info = WaveInfo {
+      audio_format: self.data_format(),
+      channels:     channels.len() as u16,
+      valid_bps:    None,
+      channel_mask: None,
+      subformat:    None,
+      ..self.info()
+    };
+
+    write_frames_to(dest, info, self.iter().map(|frame| channels.iter().map(|&ch| frame[ch]).collect()))
+  }
+
+  /// Writes the header of a wavefile described by `info` to `writer`: the
+  /// `RIFF` header, the `fmt ` chunk as produced by
+  /// `WaveInfo::to_fmt_chunk_bytes()`, and the header of the `data` chunk.
+  /// Interleaved little-endian samples can be written directly afterwards.
+  /// This is meant for custom streaming writers; `WaveWriter` takes care of
+  /// all of this by itself.
+  ///
+  /// The sizes of the `RIFF` and `data` chunks are written as `0xFFFFFFFF`,
+  /// which many readers treat as "until the end of the file".  Callers that
+  /// can seek should patch them once all samples are written: the `RIFF`
+  /// size at offset 4 with the file size minus 8, and the `data` size in the
+  /// four bytes before the first sample with the number of sample bytes.
+  ///
+  /// As with `WaveWriter::new()`, `block_align` and `byte_rate` are derived
+  /// from the other fields of `info`, and `WaveError::Unsupported` is
+  /// returned for formats other than 8, 16, 24 or 32-bit PCM and 32 or
+  /// 64-bit IEEE Float, or if the derived fields overflow.
+  ///
+  /// Since the sizes are 32-bit, the audio data must not exceed 4 GiB.  Larger
+  /// files require the RF64 format, which this function does not write.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use wavefile::{WaveFile,WaveInfo};
+  ///
+  /// let mut header = Vec::new();
+  /// WaveFile::write_standard_header(&mut header, &WaveInfo::default()).unwrap();
+  ///
+  /// assert_eq!(&header[..4], b"RIFF");
+  /// assert_eq!(header.len(), 44);
+  /// ```
+  pub fn write_standard_header<W: Write>(writer: &mut W, info: &WaveInfo) -> Result<(), WaveError> {
+    let info = validate(info)?;
+
+    writer.write_all(b"RIFF\xff\xff\xff\xffWAVE")?;
+    writer.write_all(&info.to_fmt_chunk_bytes())?;
+    writer.write_all(b"data\xff\xff\xff\xff")?;
+
+    Ok(())
+  }
+
+  /// Appends a chunk with the given `id` and `data` to the end of the
+  /// existing wavefile at `path`, and updates the size in the `RIFF`
+  /// header; e.g. to add `iXML`, `bext` or `cue ` metadata without
+  /// rewriting the audio data.  The chunk is padded to an even size.
+  ///
+  /// Only `RIFF` and `RIFX` files are supported; `RF64` and Wave64 files
+  /// return `WaveError::Unsupported`.  Any error returned includes `path`
+  /// for context.
+  ///
+  /// # Example
+  ///
+  /// ```no_run
+  /// use wavefile::WaveFile;
+  ///
+  /// let ixml = b"<BWFXML><PROJECT>Demo</PROJECT></BWFXML>";
+  ///
+  /// WaveFile::append_chunk_to_file("/tmp/take-1.wav", *b"iXML", ixml).unwrap();
+  /// ```
+  pub fn append_chunk_to_file<P: AsRef<Path>>(path: P, id: [u8; 4], data: &[u8]) -> Result<(), WaveError> {
+    let append = |path: &Path| -> Result<(), WaveError> {
+      let mut file = fs::OpenOptions::new().read(true).write(true).open(path)?;
+
+      let mut header = [0; 12];
+      file.read_exact(&mut header).map_err(|_| WaveError::NotAWavFile)?;
+
+      if header[0..4] == W64_RIFF[0..4] || LittleEndian::read_u32(&header[0..4]) == RF64 {
+        return Err(WaveError::Unsupported("Chunks can only be appended to RIFF and RIFX files".into()));
+      }
+
+      if LittleEndian::read_u32(&header[8..12]) != WAVE {
+        return Err(WaveError::NotAWavFile);
+      }
+
+      match LittleEndian::read_u32(&header[0..4]) {
+        RIFF => append_chunk::<LittleEndian>(&mut file, id, data),
+        RIFX => append_chunk::<BigEndian>(&mut file, id, data),
+        _    => Err(WaveError::NotAWavFile)
+      }
+    };
+
+    append(path.as_ref()).map_err(|e| e.with_path(path))
+  }
+}
+
+// checks that `info` describes a format `WaveWriter` can write, and returns
+// it with `block_align` and `byte_rate` derived from the other fields.
+fn validate(info: &WaveInfo) -> Result<WaveInfo, WaveError> {
+  let bps = info.bits_per_sample;
+
+  let supported = match info.data_format() {
+    Format::PCM       => bps == 8 || bps == 16 || bps == 24 || bps == 32,
+    Format::IEEEFloat => bps == 32 || bps == 64,
+    _                 => false
+  };
+
+  if !supported {
+    let msg = format!("Cannot write {}-bit {} data", bps, info.data_format());
+    return Err(WaveError::Unsupported(msg));
+  }
+
+  if info.channels == 0 {
+    return Err(WaveError::Unsupported("Cannot write a file without channels".into()));
+  }
+
+  let block_align = info.channels.checked_mul(bps / 8);
+  let byte_rate   = block_align.and_then(|align| info.sample_rate.checked_mul(align as u32));
+
+  match (block_align, byte_rate) {
+    (Some(block_align), Some(byte_rate)) => Ok(WaveInfo { block_align, byte_rate, ..*info }),
+    _ => {
+      let msg = format!("Cannot write {} channel(s) of {}-bit data at {} Hz", info.channels, bps, info.sample_rate);
+      Err(WaveError::Unsupported(msg))
+    }
+  }
+}
+
+// walks the chunk headers of `file` to find the end of the last chunk, then
+// writes the new chunk there and updates the RIFF size.
+fn append_chunk<B: ByteOrder>(file: &mut File, id: [u8; 4], data: &[u8]) -> Result<(), WaveError> {
+  let len     = file.metadata()?.len();
+  let mut end = 12;
+
+  while end + 8 <= len {
+    let mut header = [0; 8];
+    file.seek(SeekFrom::Start(end))?;
+    file.read_exact(&mut header)?;
+
+    let size = B::read_u32(&header[4..8]) as u64;
+    end += 8 + size + (size & 1);
+  }
+
+  // only the pad byte of the last chunk may be missing.
+  if end > len + 1 {
+    return Err(WaveError::parse_at("Last chunk is truncated", len));
+  }
+
+  let size    = data.len() as u64;
+  let new_end = end + 8 + size + (size & 1);
+
+  if new_end - 8 > u32::MAX as u64 {
+    let msg = format!("A chunk of {} bytes does not fit in a RIFF file", size);
+    return Err(WaveError::Unsupported(msg));
+  }
+
+  let mut bytes = [0; 4];
+
+  file.seek(SeekFrom::Start(cmp::min(end, len)))?;
+
+  if end > len {
+    file.write_all(&[0])?;
+  }
+
+  file.write_all(&id)?;
+  B::write_u32(&mut bytes, size as u32);
+  file.write_all(&bytes)?;
+  file.write_all(data)?;
+
+  if size & 1 == 1 {
+    file.write_all(&[0])?;
+  }
+
+  file.set_len(new_end)?;
+  file.seek(SeekFrom::Start(4))?;
+  B::write_u32(&mut bytes, (new_end - 8) as u32);
+  file.write_all(&bytes)?;
+
+  Ok(())
+}
+
+// validates `info` before creating `dest`, so that no file is left behind
+// for an unsupported format, then writes all `frames` to it.
+fn write_frames_to<P: AsRef<Path>, I: Iterator<Item = Frame>>(dest: P, info: WaveInfo, frames: I) -> Result<(), WaveError> {
+  validate(&info).map_err(|e| e.with_path(&dest))?;
+
+  let mut writer = WaveWriter::create(&dest, info)?;
+
+  let write = |writer: &mut WaveWriter<BufWriter<File>>| -> Result<(), WaveError> {
+    for frame in frames {
+      writer.write_frame(&frame)?;
+    }
+
+    Ok(())
+  };
+
+  write(&mut writer)
+    .and_then(|_| writer.finalize())
+    .and_then(|writer| writer.into_inner().map_err(|e| e.into_error().into()))
+    .map(|_| ())
+    .map_err(|e| e.with_path(&dest))
+}
+
+#[cfg(test)]
+use std::io::Cursor;
+#[cfg(test)]
+use super::temp_path;
+
+#[test]
+fn test_write_formats() {
+  let frames = [[0.0, -1.0], [0.5, -0.25], [0.998, 0.125]];
+
+  for &(format, bits_per_sample) in &[(Format::PCM, 8), (Format::PCM, 16), (Format::PCM, 24),
+                                      (Format::PCM, 32), (Format::IEEEFloat, 32), (Format::IEEEFloat, 64)] {
+    let info = WaveInfo { audio_format: format, bits_per_sample, ..WaveInfo::default() };
+    let mut writer = WaveWriter::new(Cursor::new(Vec::new()), info).unwrap();
+
+    for frame in &frames {
+      writer.write_frame(frame).unwrap();
+    }
+
+    let file = WaveFile::from_vec(writer.finalize().unwrap().into_inner()).unwrap();
+
+    assert_eq!(file.data_format(), format);
+    assert_eq!(file.bits_per_sample(), bits_per_sample);
+    assert_eq!(file.len(), 3);
+
+    for (written, read) in frames.iter().zip(file.iter()) {
+      for (a, b) in written.iter().zip(read.iter()) {
+        assert!((a - b).abs() < 0.01, "{} != {} for {}-bit {}", a, b, bits_per_sample, format);
+      }
+    }
+  }
+}
+
+#[test]
+fn test_write_errors() {
+  let info = WaveInfo { bits_per_sample: 12, ..WaveInfo::default() };
+  assert!(WaveWriter::new(Cursor::new(Vec::new()), info).is_err());
+
+  let info = WaveInfo { audio_format: Format::Unknown(2), ..WaveInfo::default() };
+  assert!(WaveWriter::new(Cursor::new(Vec::new()), info).is_err());
+
+  // neither block_align nor byte_rate fit in their header fields.
+  for &(channels, sample_rate) in &[(40000, 44100), (1000, 4_000_000_000)] {
+    let info = WaveInfo { channels, sample_rate, bits_per_sample: 32, ..WaveInfo::default() };
+
+    match WaveWriter::new(Cursor::new(Vec::new()), info) {
+      Err(WaveError::Unsupported(_)) => (),
+      other => panic!("Expected an Unsupported error, got {:?}", other.err())
+    }
+  }
+
+  let mut writer = WaveWriter::new(Cursor::new(Vec::new()), WaveInfo::default()).unwrap();
+  assert!(writer.write_frame(&[0.0]).is_err());
+}
+
+#[test]
+fn test_write_metadata() {
+  let metadata = Metadata {
+    title:  Some("Test Tone".into()),
+    artist: Some("wavefile".into()),
+    ..Metadata::default()
+  };
+
+  let mut writer = WaveWriter::new(Cursor::new(Vec::new()), WaveInfo::default()).unwrap();
+  writer.set_metadata(&metadata);
+  writer.write_frame(&[0.0, 0.0]).unwrap();
+
+  let file = WaveFile::from_vec(writer.finalize().unwrap().into_inner()).unwrap();
+  let ids  = file.chunks_iter().map(|chunk| chunk.id).collect::<Vec<_>>();
+  let list = file.chunks_iter().find(|chunk| chunk.id == 0x5453494c).unwrap();
+
+  assert_eq!(ids, [0x20746d66, 0x5453494c, 0x61746164]);
+  assert_eq!(list.data, &b"INFOINAM\x0a\0\0\0Test Tone\0IART\x09\0\0\0wavefile\0\0"[..]);
+  assert_eq!(file.len(), 1);
+
+  // metadata set after the audio data has been started ends up after it.
+  let mut writer = WaveWriter::new(Cursor::new(Vec::new()), WaveInfo::default()).unwrap();
+  writer.write_frame(&[0.0, 0.0]).unwrap();
+  writer.set_metadata(&metadata);
+
+  let file = WaveFile::from_vec(writer.finalize().unwrap().into_inner()).unwrap();
+  let ids  = file.chunks_iter().map(|chunk| chunk.id).collect::<Vec<_>>();
+
+  assert_eq!(ids, [0x20746d66, 0x61746164, 0x5453494c]);
+}
+
+#[test]
+fn test_write_to_path() {
+  let path = temp_path("writer-transcode.wav");
+
+  let source = WaveFile::open("./fixtures/test-s24le.wav").unwrap();
+  source.write_to_path(&path, Format::PCM, 16).unwrap();
+
+  let file = WaveFile::open(&path).unwrap();
+
+  assert_eq!(file.bits_per_sample(), 16);
+  assert_eq!(file.len(), source.len());
+  assert_eq!(file.sample_rate(), source.sample_rate());
+
+  for (a, b) in source.iter().zip(file.iter()) {
+    for (x, y) in a.iter().zip(b.iter()) {
+      assert!((x - y).abs() <= 1.0 / 32768.0, "{} != {}", x, y);
+    }
+  }
+
+  let err = source.write_to_path(&path, Format::PCM, 12).err().unwrap();
+  assert_eq!(err.path(), Some(path.as_path()));
+  assert!(WaveFile::open(&path).is_ok());
+
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_export_channel_as_mono_wav() {
+  let path   = temp_path("writer-export-channel.wav");
+  let source = WaveFile::open("./fixtures/test-s16be-rifx.wav").unwrap();
+
+  source.export_channel_as_mono_wav(1, &path).unwrap();
+
+  let file = WaveFile::open(&path).unwrap();
+
+  assert_eq!(file.channels(), 1);
+  assert_eq!(file.bits_per_sample(), 16);
+  assert_eq!(file.len(), source.len());
+  assert!(file.iter().zip(source.iter()).all(|(mono, stereo)| mono[0] == stereo[1]));
+
+  match source.export_channel_as_mono_wav(2, &path) {
+    Err(WaveError::InFile(_, ref e)) if matches!(**e, WaveError::Incompatible(_)) => (),
+    other => panic!("Expected an Incompatible error, got {:?}", other.err())
+  }
+
+  let err = source.export_channel_as_mono_wav(0, "./fixtures/missing/out.wav").err().unwrap();
+  assert!(err.path().is_some());
+
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_export_stereo_pair() {
+  let path   = temp_path("writer-export-pair.wav");
+  let source = WaveFile::open("./fixtures/test-f32le.wav").unwrap();
+
+  source.export_stereo_pair(1, 0, &path).unwrap();
+
+  let file = WaveFile::open(&path).unwrap();
+
+  assert_eq!(file.channels(), 2);
+  assert_eq!(file.data_format(), Format::IEEEFloat);
+  assert_eq!(file.len(), source.len());
+  assert!(file.iter().zip(source.iter()).all(|(pair, frame)| pair == [frame[1], frame[0]]));
+
+  assert!(source.export_stereo_pair(0, 2, &path).is_err());
+  assert!(source.export_stereo_pair(5, 1, &path).is_err());
+
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_write_standard_header() {
+  let info = WaveInfo { channels: 1, sample_rate: 8000, ..WaveInfo::default() };
+
+  let mut bytes = Vec::new();
+  WaveFile::write_standard_header(&mut bytes, &info).unwrap();
+
+  assert_eq!(bytes.len(), 44);
+  assert_eq!(&bytes[4..8], [0xff; 4]);
+  assert_eq!(&bytes[40..44], [0xff; 4]);
+
+  for sample in [0i16, 16384, -16384] {
+    bytes.extend_from_slice(&sample.to_le_bytes());
+  }
+
+  // once the sizes are patched, the result is a regular wavefile.
+  let riff_size = (bytes.len() - 8) as u32;
+  bytes[4..8].copy_from_slice(&riff_size.to_le_bytes());
+  bytes[40..44].copy_from_slice(&6u32.to_le_bytes());
+
+  let file = WaveFile::from_vec(bytes).unwrap();
+
+  assert_eq!(file.sample_rate(), 8000);
+  assert_eq!(file.iter().collect::<Vec<_>>(), [[0.0], [0.5], [-0.5]]);
+
+  let info = WaveInfo { bits_per_sample: 12, ..WaveInfo::default() };
+  assert!(WaveFile::write_standard_header(&mut Vec::new(), &info).is_err());
+
+  let info = WaveInfo { channels: 40000, bits_per_sample: 32, ..WaveInfo::default() };
+  assert!(WaveFile::write_standard_header(&mut Vec::new(), &info).is_err());
+}
+
+#[test]
+fn test_append_chunk_to_file() {
+  for name in ["test-s24le.wav", "test-s16be-rifx.wav"] {
+    let path = temp_path(&format!("writer-append-{}", name));
+    fs::copy(format!("./fixtures/{}", name), &path).unwrap();
+
+    WaveFile::append_chunk_to_file(&path, *b"iXML", b"<BWFXML/>").unwrap();
+    WaveFile::append_chunk_to_file(&path, *b"test", b"ab").unwrap();
+
+    let file     = WaveFile::open(&path).unwrap();
+    let original = WaveFile::open(format!("./fixtures/{}", name)).unwrap();
+    let chunks   = file.chunks_iter().collect::<Vec<_>>();
+    let count    = chunks.len();
+
+    assert_eq!(&chunks[count - 2].id.to_le_bytes(), b"iXML");
+    assert_eq!(chunks[count - 2].data, b"<BWFXML/>");
+    assert_eq!(&chunks[count - 1].id.to_le_bytes(), b"test");
+    assert_eq!(chunks[count - 1].data, b"ab");
+    assert_eq!(chunks[count - 1].offset + 2, fs::metadata(&path).unwrap().len());
+    assert_eq!(file.iter().collect::<Vec<_>>(), original.iter().collect::<Vec<_>>());
+
+    fs::remove_file(&path).unwrap();
+  }
+
+  let err = WaveFile::append_chunk_to_file("./Cargo.toml", *b"test", b"").err().unwrap();
+  assert!(matches!(*err.inner(), WaveError::NotAWavFile));
+  assert!(WaveFile::append_chunk_to_file("./fixtures/missing.wav", *b"test", b"").is_err());
+}
+
+#[test]
+fn test_create() {
+  let path = temp_path("writer-create.wav");
+
+  let mut writer = WaveWriter::create(&path, WaveInfo::default()).unwrap();
+  writer.write_frame(&[0.25, -0.25]).unwrap();
+  writer.finalize().unwrap();
+
+  let file = WaveFile::open(&path).unwrap();
+  assert_eq!(file.at(0), Some(vec![0.25, -0.25]));
+
+  let err = WaveWriter::create("./fixtures/missing/out.wav", WaveInfo::default()).err().unwrap();
+  assert!(err.path().is_some());
+
+  fs::remove_file(&path).unwrap();
+}